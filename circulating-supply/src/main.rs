@@ -4,7 +4,8 @@ use std::time::{Duration, SystemTime};
 
 use anyhow::Context;
 use bigdecimal::{BigDecimal, ToPrimitive};
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
+use clap::{Parser, Subcommand};
 use tracing::{error, info, warn};
 
 use near_jsonrpc_client::{methods, JsonRpcClient};
@@ -22,9 +23,45 @@ const RETRY_DURATION: Duration = Duration::from_secs(60 * 60 * 2);
 
 const CIRCULATING_SUPPLY: &str = "circulating_supply";
 
+/// Computes circulating supply and daily aggregations.
+/// Runs the daily computations if no subcommand is given
+#[derive(Parser, Debug)]
+#[clap(disable_help_subcommand(true), next_line_help(true))]
+struct Opts {
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+// The dates are UTC days (YYYY-MM-DD), the supply of the day is computed
+// for the last block before the start of the day, the same as the daily computations do
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Compute circulating supply for the day, the already stored value is kept
+    Compute {
+        #[clap(long)]
+        date: NaiveDate,
+    },
+    /// Compute circulating supply for the days (inclusive) overwriting the stored values
+    Recompute {
+        #[clap(long)]
+        from: NaiveDate,
+        #[clap(long)]
+        to: NaiveDate,
+    },
+    /// Compute circulating supply for the days (inclusive) and compare with the stored values.
+    /// Nothing is written, fails if any day differs
+    Verify {
+        #[clap(long)]
+        from: NaiveDate,
+        #[clap(long)]
+        to: NaiveDate,
+    },
+}
+
 #[actix::main]
 async fn main() -> anyhow::Result<()> {
     dotenv::dotenv().ok();
+    let opts = Opts::parse();
 
     let subscriber = tracing_subscriber::Registry::default()
         .with(tracing_subscriber::EnvFilter::from_default_env());
@@ -46,10 +83,47 @@ async fn main() -> anyhow::Result<()> {
         std::env::var("RPC_URL").expect("RPC_URL must be set in either .env or environment"),
     );
 
-    info!(target: crate::CIRCULATING_SUPPLY, "Starting calculations");
-
-    run_circulating_supply_computation(rpc_client, pool).await;
-    Ok(())
+    match opts.command {
+        None => {
+            info!(target: crate::CIRCULATING_SUPPLY, "Starting calculations");
+            run_daily_computations(rpc_client, pool).await;
+            Ok(())
+        }
+        Some(Command::Compute { date }) => {
+            ensure_blocks_are_final(&rpc_client, &date).await?;
+            check_and_collect_daily_circulating_supply(&rpc_client, &pool, &day_start(&date))
+                .await?;
+            Ok(())
+        }
+        Some(Command::Recompute { from, to }) => {
+            anyhow::ensure!(from <= to, "--from {} is after --to {}", from, to);
+            ensure_blocks_are_final(&rpc_client, &to).await?;
+            let mut date = from;
+            while date <= to {
+                recompute_daily_circulating_supply(&rpc_client, &pool, &date).await?;
+                date = date.succ_opt().context("The date is out of range")?;
+            }
+            Ok(())
+        }
+        Some(Command::Verify { from, to }) => {
+            anyhow::ensure!(from <= to, "--from {} is after --to {}", from, to);
+            ensure_blocks_are_final(&rpc_client, &to).await?;
+            let mut mismatched_days_count = 0;
+            let mut date = from;
+            while date <= to {
+                if !verify_daily_circulating_supply(&rpc_client, &pool, &date).await? {
+                    mismatched_days_count += 1;
+                }
+                date = date.succ_opt().context("The date is out of range")?;
+            }
+            anyhow::ensure!(
+                mismatched_days_count == 0,
+                "Circulating supply differs for {} days",
+                mismatched_days_count
+            );
+            Ok(())
+        }
+    }
 }
 
 // Compute circulating supply on a daily basis, starting from 13 Oct 2020
@@ -57,7 +131,9 @@ async fn main() -> anyhow::Result<()> {
 // Circulating supply is calculated by the formula:
 // total_supply - sum(locked_tokens_on_each_lockup) - sum(locked_foundation_account)
 // The value is always computed for the last block in a day (UTC).
-pub async fn run_circulating_supply_computation(
+// The daily network stats are computed for the same day right after the circulating supply,
+// then the supply changes of the epochs finished during that day.
+pub async fn run_daily_computations(
     rpc_client: JsonRpcClient,
     pool: explorer_database::actix_diesel::Database<explorer_database::diesel::PgConnection>,
 ) {
@@ -85,15 +161,20 @@ pub async fn run_circulating_supply_computation(
         }
         wait_for_loading_needed_blocks(&rpc_client, &day_to_compute).await;
 
-        match check_and_collect_daily_circulating_supply(&rpc_client, &pool, &day_to_compute).await
-        {
+        let computation_result = async {
+            check_and_collect_daily_circulating_supply(&rpc_client, &pool, &day_to_compute).await?;
+            check_and_collect_daily_stats(&pool, &day_to_compute).await?;
+            collect_epoch_supply(&pool, &day_to_compute).await
+        }
+        .await;
+        match computation_result {
             Ok(_) => {
                 day_to_compute = day_to_compute.add(DAY);
             }
             Err(err) => {
                 error!(
                     target: crate::CIRCULATING_SUPPLY,
-                    "Failed to compute circulating supply for {}: {:#}. Retry in {} hours",
+                    "Failed to compute daily aggregations for {}: {:#}. Retry in {} hours",
                     NaiveDateTime::from_timestamp(day_to_compute.as_secs() as i64, 0).date(),
                     err,
                     RETRY_DURATION.as_secs() / 60 / 60,
@@ -109,11 +190,8 @@ async fn check_and_collect_daily_circulating_supply(
     pool: &explorer_database::actix_diesel::Database<explorer_database::diesel::PgConnection>,
     request_datetime: &Duration,
 ) -> anyhow::Result<Option<models::aggregated::circulating_supply::CirculatingSupply>> {
-    let start_of_day =
-        request_datetime.as_nanos() - request_datetime.as_nanos() % crate::DAY.as_nanos();
     let printable_date = NaiveDateTime::from_timestamp(request_datetime.as_secs() as i64, 0).date();
-    let block =
-        adapters::blocks::get_latest_block_before_timestamp(pool, start_of_day as u64).await?;
+    let block = get_block_for_day(pool, request_datetime).await?;
     let block_timestamp = block
         .block_timestamp
         .to_u64()
@@ -157,6 +235,221 @@ async fn check_and_collect_daily_circulating_supply(
     }
 }
 
+async fn recompute_daily_circulating_supply(
+    rpc_client: &JsonRpcClient,
+    pool: &explorer_database::actix_diesel::Database<explorer_database::diesel::PgConnection>,
+    date: &NaiveDate,
+) -> anyhow::Result<()> {
+    let block = get_block_for_day(pool, &day_start(date)).await?;
+    let previous_supply =
+        adapters::aggregated::circulating_supply::get_circulating_supply_for_timestamp(
+            pool,
+            block
+                .block_timestamp
+                .to_u64()
+                .context("`block_timestamp` expected to be u64")?,
+        )
+        .await?;
+
+    let supply = compute_circulating_supply_for_block(pool, rpc_client, &block).await?;
+    adapters::aggregated::circulating_supply::replace_circulating_supply(pool, &supply).await?;
+    match previous_supply {
+        Some(previous_supply) => info!(
+            target: crate::CIRCULATING_SUPPLY,
+            "Circulating supply for {} is recomputed: {} (was {})",
+            date,
+            supply.circulating_tokens_supply,
+            previous_supply.circulating_tokens_supply
+        ),
+        None => info!(
+            target: crate::CIRCULATING_SUPPLY,
+            "Circulating supply for {} is computed: {}", date, supply.circulating_tokens_supply
+        ),
+    }
+    Ok(())
+}
+
+// Returns whether the stored values match the computed ones
+async fn verify_daily_circulating_supply(
+    rpc_client: &JsonRpcClient,
+    pool: &explorer_database::actix_diesel::Database<explorer_database::diesel::PgConnection>,
+    date: &NaiveDate,
+) -> anyhow::Result<bool> {
+    let block = get_block_for_day(pool, &day_start(date)).await?;
+    let stored_supply =
+        match adapters::aggregated::circulating_supply::get_circulating_supply_for_timestamp(
+            pool,
+            block
+                .block_timestamp
+                .to_u64()
+                .context("`block_timestamp` expected to be u64")?,
+        )
+        .await?
+        {
+            Some(stored_supply) => stored_supply,
+            None => {
+                warn!(
+                    target: crate::CIRCULATING_SUPPLY,
+                    "Circulating supply for {} is not stored", date
+                );
+                return Ok(false);
+            }
+        };
+
+    let supply = compute_circulating_supply_for_block(pool, rpc_client, &block).await?;
+    let differences: Vec<String> = [
+        (
+            "circulating_tokens_supply",
+            stored_supply.circulating_tokens_supply.to_string(),
+            supply.circulating_tokens_supply.to_string(),
+        ),
+        (
+            "total_tokens_supply",
+            stored_supply.total_tokens_supply.to_string(),
+            supply.total_tokens_supply.to_string(),
+        ),
+        (
+            "total_lockup_contracts_count",
+            stored_supply.total_lockup_contracts_count.to_string(),
+            supply.total_lockup_contracts_count.to_string(),
+        ),
+        (
+            "unfinished_lockup_contracts_count",
+            stored_supply.unfinished_lockup_contracts_count.to_string(),
+            supply.unfinished_lockup_contracts_count.to_string(),
+        ),
+        (
+            "foundation_locked_tokens",
+            stored_supply.foundation_locked_tokens.to_string(),
+            supply.foundation_locked_tokens.to_string(),
+        ),
+        (
+            "lockups_locked_tokens",
+            stored_supply.lockups_locked_tokens.to_string(),
+            supply.lockups_locked_tokens.to_string(),
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, stored, computed)| stored != computed)
+    .map(|(field, stored, computed)| format!("{}: stored {}, computed {}", field, stored, computed))
+    .collect();
+
+    if differences.is_empty() {
+        info!(
+            target: crate::CIRCULATING_SUPPLY,
+            "Circulating supply for {} matches", date
+        );
+        Ok(true)
+    } else {
+        warn!(
+            target: crate::CIRCULATING_SUPPLY,
+            "Circulating supply for {} differs: {}",
+            date,
+            differences.join("; ")
+        );
+        Ok(false)
+    }
+}
+
+// The circulating supply of the day is computed for the last block before the start of the day
+async fn get_block_for_day(
+    pool: &explorer_database::actix_diesel::Database<explorer_database::diesel::PgConnection>,
+    request_datetime: &Duration,
+) -> anyhow::Result<models::Block> {
+    let start_of_day =
+        request_datetime.as_nanos() - request_datetime.as_nanos() % crate::DAY.as_nanos();
+    adapters::blocks::get_latest_block_before_timestamp(pool, start_of_day as u64).await
+}
+
+fn day_start(date: &NaiveDate) -> Duration {
+    Duration::from_secs(
+        date.and_hms_opt(0, 0, 0)
+            .expect("Midnight expected to be valid")
+            .timestamp() as u64,
+    )
+}
+
+// Otherwise the latest indexed block would be taken for the future dates
+async fn ensure_blocks_are_final(
+    rpc_client: &JsonRpcClient,
+    date: &NaiveDate,
+) -> anyhow::Result<()> {
+    let final_block_timestamp = get_final_block_timestamp(rpc_client).await?;
+    anyhow::ensure!(
+        final_block_timestamp > day_start(date),
+        "Blocks are not final yet to compute circulating supply for {}",
+        date
+    );
+    Ok(())
+}
+
+// The stats are computed for the whole UTC day which ends at the start of the day of `request_datetime`,
+// i.e. the day ending with the block used for the circulating supply
+async fn check_and_collect_daily_stats(
+    pool: &explorer_database::actix_diesel::Database<explorer_database::diesel::PgConnection>,
+    request_datetime: &Duration,
+) -> anyhow::Result<()> {
+    let start_of_day = (request_datetime.as_nanos()
+        - request_datetime.as_nanos() % crate::DAY.as_nanos()
+        - crate::DAY.as_nanos()) as u64;
+    let printable_date =
+        NaiveDateTime::from_timestamp((start_of_day / 1_000_000_000) as i64, 0).date();
+
+    if adapters::aggregated::daily_stats::get_daily_stats(pool, start_of_day)
+        .await?
+        .is_some()
+    {
+        info!(
+            target: crate::CIRCULATING_SUPPLY,
+            "Daily stats for {} were already computed", printable_date,
+        );
+        return Ok(());
+    }
+
+    info!(
+        target: crate::CIRCULATING_SUPPLY,
+        "Computing daily stats for {}", printable_date,
+    );
+    // The circulating supply of the next days should not wait for the missing blocks
+    match adapters::aggregated::daily_stats::compute_daily_stats(pool, start_of_day).await? {
+        Some(stats) => info!(
+            target: crate::CIRCULATING_SUPPLY,
+            "Daily stats for {}: {} transactions, {} active accounts, {} new accounts",
+            printable_date,
+            stats.transactions_count,
+            stats.active_accounts_count,
+            stats.new_accounts_count,
+        ),
+        None => warn!(
+            target: crate::CIRCULATING_SUPPLY,
+            "No blocks are indexed for {}, daily stats are skipped", printable_date,
+        ),
+    }
+    Ok(())
+}
+
+// Minted and burnt tokens are stored for each epoch finished before the start of the day
+// of `request_datetime`, including the ones missed on the previous days
+async fn collect_epoch_supply(
+    pool: &explorer_database::actix_diesel::Database<explorer_database::diesel::PgConnection>,
+    request_datetime: &Duration,
+) -> anyhow::Result<()> {
+    let start_of_day =
+        (request_datetime.as_nanos() - request_datetime.as_nanos() % crate::DAY.as_nanos()) as u64;
+    let printable_date =
+        NaiveDateTime::from_timestamp((start_of_day / 1_000_000_000) as i64, 0).date();
+
+    let computed_epochs_count =
+        adapters::aggregated::epoch_supply::compute_epoch_supply(pool, start_of_day).await?;
+    info!(
+        target: crate::CIRCULATING_SUPPLY,
+        "Supply changes of {} epochs finished before {} are computed",
+        computed_epochs_count,
+        printable_date,
+    );
+    Ok(())
+}
+
 async fn compute_circulating_supply_for_block(
     pool: &explorer_database::actix_diesel::Database<explorer_database::diesel::PgConnection>,
     rpc_client: &JsonRpcClient,
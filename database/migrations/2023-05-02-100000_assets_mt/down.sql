@@ -0,0 +1,2 @@
+DROP TABLE assets__multi_token_events;
+DROP TYPE mt_event_kind;
@@ -0,0 +1,69 @@
+CREATE TYPE mt_event_kind AS ENUM (
+    'MINT',
+    'TRANSFER',
+    'BURN'
+    );
+
+CREATE TABLE assets__multi_token_events
+(
+    emitted_for_receipt_id                text           NOT NULL,
+
+    -- Next three columns (emitted_at_block_timestamp, emitted_in_shard_id, emitted_index_of_event_entry_in_shard)
+    -- should be used for sorting purposes, at the order that we just named.
+    emitted_at_block_timestamp            numeric(20, 0) NOT NULL,
+    emitted_in_shard_id                   numeric(20, 0) NOT NULL,
+    -- `emitted_index_of_event_entry_in_shard` has non-trivial implementation. It combines the order from:
+    -- 1. execution_outcomes::index_in_chunk
+    -- 2. Index of current action_receipt
+    -- 3. Index of event entry that we are currently working on. Note, one receipt can have multiple events
+    --    (read: log with multiple statements), each of them can have multiple account_ids and token_ids.
+    --    We use continuous numbering for all these items.
+    emitted_index_of_event_entry_in_shard integer        NOT NULL,
+
+    -- account_id of the contract itself. In a simple words, it's the owner/creator of MT contract
+    emitted_by_contract_account_id        text           NOT NULL,
+    -- Unique ID of the token inside the contract
+    token_id                              text           NOT NULL,
+    -- NEP-245 sends `token_ids` and `amounts` as two arrays of the same length,
+    -- we flatten them so each line contains one token with its own amount
+    amount                                text           NOT NULL,
+    event_kind                            mt_event_kind  NOT NULL,
+
+    -- We use `NOT NULL DEFAULT ''` in all the lines below to simplify further issue with nulls + constraints
+    -- Previous owner of the token. Empty if we have mt_event_kind 'MINT'.
+    token_old_owner_account_id            text           NOT NULL DEFAULT '',
+    -- New owner of the token. Empty if we have mt_event_kind 'BURN'.
+    token_new_owner_account_id            text           NOT NULL DEFAULT '',
+    -- The account that initialized the event.
+    -- Optional field: filled only if the event is done NOT by token_old_owner_account_id.
+    -- Empty if we have mt_event_kind 'MINT'.
+    token_authorized_account_id           text           NOT NULL DEFAULT '',
+    -- Optional message associated with token movement.
+    event_memo                            text           NOT NULL DEFAULT ''
+);
+
+-- This set of columns is enough to identify the record
+-- The same lines could come from different indexers, that is fully legal context, we simply ignore them
+ALTER TABLE ONLY assets__multi_token_events
+    ADD CONSTRAINT assets__multi_token_events_pkey PRIMARY KEY (emitted_for_receipt_id,
+                                                                emitted_index_of_event_entry_in_shard);
+
+CREATE INDEX assets__multi_token_events_sorting_idx ON assets__multi_token_events
+    USING btree (emitted_at_block_timestamp,
+                 emitted_in_shard_id,
+                 emitted_index_of_event_entry_in_shard);
+
+CREATE INDEX assets__multi_token_events_block_timestamp_idx ON assets__multi_token_events
+    USING btree (emitted_at_block_timestamp);
+
+CREATE INDEX assets__multi_token_events_old_owner_account_id_idx ON assets__multi_token_events
+    USING btree (token_old_owner_account_id);
+
+CREATE INDEX assets__multi_token_events_new_owner_account_id_idx ON assets__multi_token_events
+    USING btree (token_new_owner_account_id);
+
+CREATE INDEX assets__mt_contract_id_idx ON assets__multi_token_events (emitted_by_contract_account_id);
+
+ALTER TABLE ONLY assets__multi_token_events
+    ADD CONSTRAINT assets__multi_token_events_fk
+        FOREIGN KEY (emitted_for_receipt_id) REFERENCES receipts (receipt_id) ON DELETE CASCADE;
@@ -0,0 +1,2 @@
+DROP TABLE assets__invalid_events;
+DROP TYPE event_invalidity_reason;
@@ -0,0 +1,52 @@
+CREATE TYPE event_invalidity_reason AS ENUM (
+    'INVALID_AMOUNT',
+    'INVALID_ACCOUNT_ID',
+    'TRANSFER_NOT_BY_OWNER',
+    'TOKEN_IDS_AND_AMOUNTS_MISMATCH'
+    );
+
+-- Filled only when the indexer runs with `--strict-events-validation`.
+-- The event entries stored here are NOT stored to assets__non_fungible_token_events,
+-- assets__fungible_token_events and assets__multi_token_events tables.
+-- FT transfers restored from the function calls (`--detect-legacy-ft-transfers`) are validated the same way,
+-- they are stored with `nep141` standard and `ft_transfer` kind
+CREATE TABLE assets__invalid_events
+(
+    emitted_for_receipt_id                text                    NOT NULL,
+
+    -- Next three columns (emitted_at_block_timestamp, emitted_in_shard_id, emitted_index_of_event_entry_in_shard)
+    -- should be used for sorting purposes, at the order that we just named.
+    emitted_at_block_timestamp            numeric(20, 0)          NOT NULL,
+    emitted_in_shard_id                   numeric(20, 0)          NOT NULL,
+    -- The invalid entries are numbered together with the lines of the same standard:
+    -- the entry takes the index it would have in the assets table of its standard,
+    -- so the valid lines around it keep their order
+    emitted_index_of_event_entry_in_shard integer                 NOT NULL,
+
+    -- account_id of the contract itself
+    emitted_by_contract_account_id        text                    NOT NULL,
+    -- `nep141`, `nep171` or `nep245`
+    event_standard                        text                    NOT NULL,
+    -- The event name as it was provided by the contract, e.g. `ft_transfer`
+    event_kind                            text                    NOT NULL,
+    -- One entry of the event `data` array, as it was provided by the contract
+    event_data                            jsonb                   NOT NULL,
+    invalidity_reason                     event_invalidity_reason NOT NULL
+);
+
+ALTER TABLE ONLY assets__invalid_events
+    ADD CONSTRAINT assets__invalid_events_pkey PRIMARY KEY (emitted_for_receipt_id,
+                                                            event_standard,
+                                                            emitted_index_of_event_entry_in_shard);
+
+CREATE INDEX assets__invalid_events_sorting_idx ON assets__invalid_events
+    USING btree (emitted_at_block_timestamp,
+                 emitted_in_shard_id,
+                 event_standard,
+                 emitted_index_of_event_entry_in_shard);
+
+CREATE INDEX assets__invalid_events_contract_id_idx ON assets__invalid_events (emitted_by_contract_account_id);
+
+ALTER TABLE ONLY assets__invalid_events
+    ADD CONSTRAINT assets__invalid_events_fk
+        FOREIGN KEY (emitted_for_receipt_id) REFERENCES receipts (receipt_id) ON DELETE CASCADE;
@@ -0,0 +1,4 @@
+DELETE FROM deprecated.assets__fungible_token_events WHERE derived_from_function_call;
+
+ALTER TABLE deprecated.assets__fungible_token_events
+    DROP COLUMN derived_from_function_call;
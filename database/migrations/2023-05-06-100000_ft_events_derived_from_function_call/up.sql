@@ -0,0 +1,9 @@
+-- TRUE for the lines synthesized by the indexer running with `--detect-legacy-ft-transfers`.
+-- Such lines describe the transfers of NEP-141 tokens which never emitted `EVENT_JSON` logs,
+-- they are restored from `ft_transfer`, `ft_transfer_call` and `ft_resolve_transfer` function calls
+-- of the contracts listed in `legacy_ft_contracts` of the assets filter file.
+-- For all happy users of Postgres 11+, this should run fast
+ALTER TABLE deprecated.assets__fungible_token_events
+    ADD COLUMN derived_from_function_call BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE deprecated.assets__fungible_token_events ALTER COLUMN derived_from_function_call DROP DEFAULT;
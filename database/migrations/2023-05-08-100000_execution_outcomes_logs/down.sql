@@ -0,0 +1,2 @@
+ALTER TABLE execution_outcomes
+    DROP COLUMN logs;
@@ -0,0 +1,5 @@
+-- NULL means the outcome was indexed before we started to store the logs,
+-- such outcomes can't be used by `backfill-events` subcommand.
+-- For all happy users of Postgres 11+, this should run fast
+ALTER TABLE execution_outcomes
+    ADD COLUMN logs TEXT[];
@@ -0,0 +1,4 @@
+ALTER TABLE execution_outcomes
+    DROP COLUMN return_value_base64,
+    DROP COLUMN return_value_json,
+    DROP COLUMN failure;
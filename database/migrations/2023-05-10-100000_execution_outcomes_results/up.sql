@@ -0,0 +1,8 @@
+-- For all happy users of Postgres 11+, this should run fast
+ALTER TABLE execution_outcomes
+    -- Filled for SUCCESS_VALUE status only. Empty string means the function returned nothing
+    ADD COLUMN return_value_base64 TEXT,
+    -- The same return value, if it is a valid JSON (similar to args_json in action_receipt_actions)
+    ADD COLUMN return_value_json   JSONB,
+    -- Filled for FAILURE status only, TxExecutionError structure from nearcore
+    ADD COLUMN failure             JSONB;
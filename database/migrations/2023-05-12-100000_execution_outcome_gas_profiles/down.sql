@@ -0,0 +1 @@
+DROP TABLE execution_outcome_gas_profiles;
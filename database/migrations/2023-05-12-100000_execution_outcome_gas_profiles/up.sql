@@ -0,0 +1,23 @@
+-- Filled only when the indexer runs with `--store-gas-profiles`.
+-- Compute usage is not available in the execution outcome views we use, so it is not stored here
+CREATE TABLE execution_outcome_gas_profiles
+(
+    receipt_id           text           NOT NULL,
+    index_in_gas_profile integer        NOT NULL,
+    -- The version of the execution metadata, the set of the costs differs between the versions
+    metadata_version     integer        NOT NULL,
+    -- `ACTION_COST` or `WASM_HOST_COST`
+    cost_category        text           NOT NULL,
+    -- e.g. `FUNCTION_CALL_BASE`, `STORAGE_WRITE_VALUE_BYTE`
+    cost                 text           NOT NULL,
+    gas_used             numeric(20, 0) NOT NULL
+);
+
+ALTER TABLE ONLY execution_outcome_gas_profiles
+    ADD CONSTRAINT execution_outcome_gas_profiles_pkey PRIMARY KEY (receipt_id, index_in_gas_profile);
+
+CREATE INDEX execution_outcome_gas_profiles_cost_idx ON execution_outcome_gas_profiles (cost);
+
+ALTER TABLE ONLY execution_outcome_gas_profiles
+    ADD CONSTRAINT execution_outcome_gas_profiles_fk
+        FOREIGN KEY (receipt_id) REFERENCES execution_outcomes (receipt_id) ON DELETE CASCADE;
@@ -0,0 +1,19 @@
+-- Filled only when the indexer runs with `--store-outcome-proofs`.
+-- Allows to verify the execution outcome against the light client without requests to RPC
+CREATE TABLE execution_outcome_proofs
+(
+    receipt_id             text  NOT NULL,
+    -- The block the outcome belongs to, it is the same as execution_outcomes.executed_in_block_hash
+    executed_in_block_hash text  NOT NULL,
+    -- Merkle path of the outcome in the chunk outcomes, the list of `{"hash": ..., "direction": "Left" | "Right"}`
+    proof                  jsonb NOT NULL
+);
+
+ALTER TABLE ONLY execution_outcome_proofs
+    ADD CONSTRAINT execution_outcome_proofs_pkey PRIMARY KEY (receipt_id);
+
+CREATE INDEX execution_outcome_proofs_block_hash_idx ON execution_outcome_proofs (executed_in_block_hash);
+
+ALTER TABLE ONLY execution_outcome_proofs
+    ADD CONSTRAINT execution_outcome_proofs_fk
+        FOREIGN KEY (receipt_id) REFERENCES execution_outcomes (receipt_id) ON DELETE CASCADE;
@@ -0,0 +1,32 @@
+-- The header fields which are not stored in `blocks` table
+CREATE TABLE block_headers
+(
+    block_hash               text           NOT NULL,
+    block_height             numeric(20, 0) NOT NULL,
+    epoch_id                 text           NOT NULL,
+    next_epoch_id            text           NOT NULL,
+    -- Number of the chunks produced for this block (the rest of the shards have missing chunks)
+    chunks_included          numeric(20, 0) NOT NULL,
+    -- The list of `{"account_id": ..., "public_key": ..., "stake": ..., "validator_stake_struct_version": ...}`
+    validator_proposals      jsonb          NOT NULL,
+    -- The list of the validators slashed by the challenges
+    challenges_result        jsonb          NOT NULL,
+    latest_protocol_version  integer        NOT NULL,
+    random_value             text           NOT NULL,
+    -- NULL for the blocks produced before the block ordinal was introduced
+    block_ordinal            numeric(20, 0),
+    last_final_block_hash    text           NOT NULL,
+    last_ds_final_block_hash text           NOT NULL,
+    -- Number of the block producers who approved the previous block
+    approvals_count          integer        NOT NULL
+);
+
+ALTER TABLE ONLY block_headers
+    ADD CONSTRAINT block_headers_pkey PRIMARY KEY (block_hash);
+
+CREATE INDEX block_headers_height_idx ON block_headers (block_height);
+CREATE INDEX block_headers_epoch_id_idx ON block_headers (epoch_id);
+
+ALTER TABLE ONLY block_headers
+    ADD CONSTRAINT block_headers_fk
+        FOREIGN KEY (block_hash) REFERENCES blocks (block_hash) ON DELETE CASCADE;
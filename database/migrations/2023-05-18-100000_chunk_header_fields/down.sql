@@ -0,0 +1,12 @@
+DROP TABLE missing_chunks;
+
+ALTER TABLE chunks
+    DROP COLUMN prev_state_root,
+    DROP COLUMN outcome_root,
+    DROP COLUMN encoded_length,
+    DROP COLUMN height_created,
+    DROP COLUMN height_included,
+    DROP COLUMN balance_burnt,
+    DROP COLUMN outgoing_receipts_root,
+    DROP COLUMN tx_root,
+    DROP COLUMN validator_proposals;
@@ -0,0 +1,29 @@
+-- The columns are NULL for the chunks indexed before the migration.
+-- For all happy users of Postgres 11+, this should run fast
+ALTER TABLE chunks
+    ADD COLUMN prev_state_root        TEXT,
+    ADD COLUMN outcome_root           TEXT,
+    ADD COLUMN encoded_length         NUMERIC(20, 0),
+    ADD COLUMN height_created         NUMERIC(20, 0),
+    ADD COLUMN height_included        NUMERIC(20, 0),
+    ADD COLUMN balance_burnt          NUMERIC(45, 0),
+    ADD COLUMN outgoing_receipts_root TEXT,
+    ADD COLUMN tx_root                TEXT,
+    ADD COLUMN validator_proposals    JSONB;
+
+-- The shards which did not get the chunk in the block.
+-- Filled starting from this migration, the previous blocks are not analysed
+CREATE TABLE missing_chunks
+(
+    included_in_block_hash text           NOT NULL,
+    shard_id               numeric(20, 0) NOT NULL
+);
+
+ALTER TABLE ONLY missing_chunks
+    ADD CONSTRAINT missing_chunks_pkey PRIMARY KEY (included_in_block_hash, shard_id);
+
+CREATE INDEX missing_chunks_shard_id_idx ON missing_chunks (shard_id);
+
+ALTER TABLE ONLY missing_chunks
+    ADD CONSTRAINT missing_chunks_fk
+        FOREIGN KEY (included_in_block_hash) REFERENCES blocks (block_hash) ON DELETE CASCADE;
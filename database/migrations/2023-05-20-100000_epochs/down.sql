@@ -0,0 +1,2 @@
+DROP TABLE epoch_validators;
+DROP TABLE epochs;
@@ -0,0 +1,44 @@
+-- The epochs are registered when the indexer observes the first block of the epoch.
+-- The epoch which was in progress when the indexer started gets only the end (and the validators info, if enabled)
+CREATE TABLE epochs
+(
+    epoch_id              text           NOT NULL,
+    -- Filled only when the indexer runs with `--store-epoch-validators`
+    epoch_height          numeric(20, 0),
+    start_block_height    numeric(20, 0),
+    start_block_hash      text,
+    start_block_timestamp numeric(20, 0),
+    -- NULL for the current epoch
+    end_block_height      numeric(20, 0),
+    -- Filled only when the indexer runs with `--store-epoch-validators`
+    protocol_version      integer
+);
+
+ALTER TABLE ONLY epochs
+    ADD CONSTRAINT epochs_pkey PRIMARY KEY (epoch_id);
+
+CREATE INDEX epochs_start_block_height_idx ON epochs (start_block_height);
+
+-- Filled only when the indexer runs with `--store-epoch-validators`, once the epoch is finished
+CREATE TABLE epoch_validators
+(
+    epoch_id            text             NOT NULL,
+    account_id          text             NOT NULL,
+    public_key          text             NOT NULL,
+    stake               numeric(45, 0)   NOT NULL,
+    is_slashed          boolean          NOT NULL,
+    shards              numeric(20, 0)[] NOT NULL,
+    num_produced_blocks numeric(20, 0)   NOT NULL,
+    num_expected_blocks numeric(20, 0)   NOT NULL,
+    num_produced_chunks numeric(20, 0)   NOT NULL,
+    num_expected_chunks numeric(20, 0)   NOT NULL
+);
+
+ALTER TABLE ONLY epoch_validators
+    ADD CONSTRAINT epoch_validators_pkey PRIMARY KEY (epoch_id, account_id);
+
+CREATE INDEX epoch_validators_account_id_idx ON epoch_validators (account_id);
+
+ALTER TABLE ONLY epoch_validators
+    ADD CONSTRAINT epoch_validators_fk
+        FOREIGN KEY (epoch_id) REFERENCES epochs (epoch_id) ON DELETE CASCADE;
@@ -0,0 +1,3 @@
+DROP TABLE staking_positions;
+DROP TABLE staking_events;
+DROP TYPE staking_event_kind;
@@ -0,0 +1,55 @@
+CREATE TYPE staking_event_kind AS ENUM (
+    'DEPOSIT',
+    'STAKE',
+    'UNSTAKE',
+    'WITHDRAW'
+    );
+
+-- The events are restored from the logs of the staking pool contracts
+-- (https://github.com/near/core-contracts/tree/master/staking-pool).
+-- Only the successful receipts of the pools matching `--staking-pools` patterns are analysed
+CREATE TABLE staking_events
+(
+    receipt_id                     text               NOT NULL,
+    -- One receipt can produce several events, e.g. `deposit_and_stake` produces DEPOSIT and STAKE
+    index_in_receipt               integer            NOT NULL,
+    executed_in_block_timestamp    numeric(20, 0)     NOT NULL,
+    executed_in_block_height       numeric(20, 0)     NOT NULL,
+    pool_account_id                text               NOT NULL,
+    delegator_account_id           text               NOT NULL,
+    -- The method which reported the event, e.g. `deposit_and_stake`, `unstake_all`.
+    -- The batch receipt can call several methods, each of them gets its own events
+    method_name                    text               NOT NULL,
+    event_kind                     staking_event_kind NOT NULL,
+    amount                         numeric(45, 0)     NOT NULL,
+    -- The balances of the delegator right after the event, as the pool reported them
+    unstaked_balance_after_event   numeric(45, 0)     NOT NULL,
+    -- NULL for DEPOSIT and WITHDRAW, the pool does not report the shares for them
+    staking_shares_after_event     numeric(45, 0)
+);
+
+ALTER TABLE ONLY staking_events
+    ADD CONSTRAINT staking_events_pkey PRIMARY KEY (receipt_id, index_in_receipt);
+
+CREATE INDEX staking_events_pool_idx ON staking_events (pool_account_id, executed_in_block_timestamp);
+CREATE INDEX staking_events_delegator_idx ON staking_events (delegator_account_id, executed_in_block_timestamp);
+
+ALTER TABLE ONLY staking_events
+    ADD CONSTRAINT staking_events_fk
+        FOREIGN KEY (receipt_id) REFERENCES execution_outcomes (receipt_id) ON DELETE CASCADE;
+
+-- The latest known position of the delegator in the pool
+CREATE TABLE staking_positions
+(
+    delegator_account_id     text           NOT NULL,
+    pool_account_id          text           NOT NULL,
+    unstaked_balance         numeric(45, 0) NOT NULL,
+    -- NULL until the first STAKE or UNSTAKE event
+    staking_shares           numeric(45, 0),
+    last_update_block_height numeric(20, 0) NOT NULL
+);
+
+ALTER TABLE ONLY staking_positions
+    ADD CONSTRAINT staking_positions_pkey PRIMARY KEY (delegator_account_id, pool_account_id);
+
+CREATE INDEX staking_positions_pool_idx ON staking_positions (pool_account_id);
@@ -0,0 +1,2 @@
+DROP TABLE account_balance_changes;
+DROP FUNCTION create_account_balance_changes_partitions;
@@ -0,0 +1,55 @@
+-- Compact replacement of `deprecated.account_changes`. Only AccountUpdate state changes are stored,
+-- several updates of the account caused by the same receipt (or transaction) in a row are collapsed into the last one,
+-- and the updates which do not change the balances and the storage usage are skipped
+CREATE TABLE account_balance_changes
+(
+    account_id                 text                     NOT NULL,
+    changed_in_block_height    numeric(20, 0)           NOT NULL,
+    changed_in_block_timestamp numeric(20, 0)           NOT NULL,
+    -- The order of the change among the stored changes of the block (over all the shards)
+    index_in_block             integer                  NOT NULL,
+    caused_by_receipt_id       text,
+    caused_by_transaction_hash text,
+    update_reason              state_change_reason_kind NOT NULL,
+    nonstaked_balance          numeric(45, 0)           NOT NULL,
+    staked_balance             numeric(45, 0)           NOT NULL,
+    storage_usage              numeric(20, 0)           NOT NULL,
+    -- The partition key has to be the part of the primary key
+    CONSTRAINT account_balance_changes_pkey PRIMARY KEY (changed_in_block_height, index_in_block)
+) PARTITION BY RANGE (changed_in_block_height);
+
+CREATE INDEX account_balance_changes_account_idx ON account_balance_changes (account_id, changed_in_block_height);
+CREATE INDEX account_balance_changes_timestamp_idx ON account_balance_changes (changed_in_block_timestamp);
+CREATE INDEX account_balance_changes_receipt_idx ON account_balance_changes (caused_by_receipt_id);
+
+-- 10M blocks (~4 months on mainnet) per partition.
+-- There is no DEFAULT partition on purpose: once it has rows, the partitions for their range can't be created.
+-- Instead, the indexer calls this function to create the partition of the block it stores and the next one,
+-- so the next partition is ready ~10M blocks in advance. It can be called manually as well, e.g.
+-- SELECT create_account_balance_changes_partitions(200000000, 250000000);
+CREATE FUNCTION create_account_balance_changes_partitions(from_block_height numeric, to_block_height numeric)
+    RETURNS void
+    LANGUAGE plpgsql
+AS
+$$
+DECLARE
+    partition_size  CONSTANT bigint := 10000000;
+    partition_start          bigint := floor(from_block_height / partition_size) * partition_size;
+    partition_name           text;
+BEGIN
+    WHILE partition_start <= to_block_height
+        LOOP
+            partition_name := 'account_balance_changes_' || lpad((partition_start / 1000000)::text, 3, '0') || 'm';
+            -- Checked before CREATE, `IF NOT EXISTS` would still lock the parent table
+            IF to_regclass(partition_name) IS NULL THEN
+                EXECUTE format(
+                        'CREATE TABLE %I PARTITION OF account_balance_changes FOR VALUES FROM (%s) TO (%s)',
+                        partition_name, partition_start, partition_start + partition_size
+                    );
+            END IF;
+            partition_start := partition_start + partition_size;
+        END LOOP;
+END;
+$$;
+
+SELECT create_account_balance_changes_partitions(0, 199999999);
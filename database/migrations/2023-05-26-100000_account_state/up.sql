@@ -0,0 +1,20 @@
+-- The latest known state of each account, so the balances can be read without the requests to RPC.
+-- The accounts which were not changed since the indexing start are absent here
+CREATE TABLE account_state
+(
+    account_id               text           NOT NULL,
+    nonstaked_balance        numeric(45, 0) NOT NULL,
+    staked_balance           numeric(45, 0) NOT NULL,
+    storage_usage            numeric(20, 0) NOT NULL,
+    -- Base58-encoded sha256 of the contract code, `11111111111111111111111111111111` if there is no contract
+    code_hash                text           NOT NULL,
+    -- The balances and the storage usage of the deleted account are zeros,
+    -- the row is kept to avoid the older updates to bring the account back
+    is_deleted               boolean        NOT NULL,
+    last_update_block_height numeric(20, 0) NOT NULL
+);
+
+ALTER TABLE ONLY account_state
+    ADD CONSTRAINT account_state_pkey PRIMARY KEY (account_id);
+
+CREATE INDEX account_state_code_hash_idx ON account_state (code_hash);
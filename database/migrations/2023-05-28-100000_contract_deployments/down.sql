@@ -0,0 +1,2 @@
+DROP TABLE contract_codes;
+DROP TABLE contract_deployments;
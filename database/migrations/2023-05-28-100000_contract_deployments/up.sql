@@ -0,0 +1,36 @@
+-- Successful DeployContract actions.
+-- `args` of the action in `action_receipt_actions` contain `code_sha256` which is the hex of the base64-encoded code,
+-- not the hash, it is kept as is for compatibility
+CREATE TABLE contract_deployments
+(
+    receipt_id                  text           NOT NULL,
+    index_in_action_receipt     integer        NOT NULL,
+    contract_account_id         text           NOT NULL,
+    -- Base58-encoded sha256 of the code, the same value as `account_state.code_hash`
+    code_hash                   text           NOT NULL,
+    code_size                   numeric(20, 0) NOT NULL,
+    deployed_in_block_height    numeric(20, 0) NOT NULL,
+    deployed_in_block_timestamp numeric(20, 0) NOT NULL
+);
+
+ALTER TABLE ONLY contract_deployments
+    ADD CONSTRAINT contract_deployments_pkey PRIMARY KEY (receipt_id, index_in_action_receipt);
+
+CREATE INDEX contract_deployments_contract_idx ON contract_deployments (contract_account_id, deployed_in_block_height);
+CREATE INDEX contract_deployments_code_hash_idx ON contract_deployments (code_hash);
+
+ALTER TABLE ONLY contract_deployments
+    ADD CONSTRAINT contract_deployments_fk
+        FOREIGN KEY (receipt_id) REFERENCES receipts (receipt_id) ON DELETE CASCADE;
+
+-- The deployed codes, each code is stored once.
+-- Filled only with `--store-contract-code` argument
+CREATE TABLE contract_codes
+(
+    code_hash text           NOT NULL,
+    code_size numeric(20, 0) NOT NULL,
+    code      bytea          NOT NULL
+);
+
+ALTER TABLE ONLY contract_codes
+    ADD CONSTRAINT contract_codes_pkey PRIMARY KEY (code_hash);
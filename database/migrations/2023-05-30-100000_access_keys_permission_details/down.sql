@@ -0,0 +1,5 @@
+ALTER TABLE access_keys
+    DROP COLUMN permission_allowance,
+    DROP COLUMN permission_receiver_id,
+    DROP COLUMN permission_method_names,
+    DROP COLUMN nonce;
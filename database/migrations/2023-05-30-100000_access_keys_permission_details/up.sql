@@ -0,0 +1,9 @@
+-- FunctionCall permission details, NULL for FULL_ACCESS keys and for the keys stored before this migration
+ALTER TABLE access_keys
+    -- NULL for FunctionCall keys means unlimited allowance
+    ADD COLUMN permission_allowance    numeric(45, 0),
+    ADD COLUMN permission_receiver_id  text,
+    -- Empty array means all the methods are allowed
+    ADD COLUMN permission_method_names text[],
+    -- The latest known nonce of the key, updated on each transaction signed with the key
+    ADD COLUMN nonce                   numeric(20, 0);
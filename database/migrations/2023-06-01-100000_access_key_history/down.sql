@@ -0,0 +1,2 @@
+DROP TABLE access_key_history;
+DROP TYPE access_key_history_action;
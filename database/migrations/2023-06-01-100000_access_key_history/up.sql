@@ -0,0 +1,36 @@
+CREATE TYPE access_key_history_action AS ENUM (
+    'ADD',
+    'DELETE',
+    'PERMISSION_CHANGE'
+    );
+
+-- Append-only history of the access keys, `access_keys` table is the projection of it (the latest state of each key).
+-- The receipt can delete and re-add the key, each change gets its own row. The key added over the existing one
+-- with the different permission is PERMISSION_CHANGE, the nonce updates of the existing key are not stored here.
+-- The keys from genesis are not here since they were not added by the receipts
+CREATE TABLE access_key_history
+(
+    public_key              text                       NOT NULL,
+    account_id              text                       NOT NULL,
+    caused_by_receipt_id    text                       NOT NULL,
+    -- The order of the key changes made by the receipt
+    index_in_receipt        integer                    NOT NULL,
+    changed_in_block_height numeric(20, 0)             NOT NULL,
+    action                  access_key_history_action  NOT NULL,
+    -- The permission columns are NULL for DELETE
+    permission_kind         access_key_permission_kind,
+    permission_allowance    numeric(45, 0),
+    permission_receiver_id  text,
+    permission_method_names text[],
+    nonce                   numeric(20, 0)
+);
+
+ALTER TABLE ONLY access_key_history
+    ADD CONSTRAINT access_key_history_pkey PRIMARY KEY (caused_by_receipt_id, index_in_receipt);
+
+CREATE INDEX access_key_history_key_idx ON access_key_history (account_id, public_key, changed_in_block_height);
+CREATE INDEX access_key_history_block_height_idx ON access_key_history (changed_in_block_height);
+
+ALTER TABLE ONLY access_key_history
+    ADD CONSTRAINT access_key_history_fk
+        FOREIGN KEY (caused_by_receipt_id) REFERENCES receipts (receipt_id) ON DELETE CASCADE;
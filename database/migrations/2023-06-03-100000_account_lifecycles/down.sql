@@ -0,0 +1,2 @@
+DROP VIEW current_accounts;
+DROP TABLE account_lifecycles;
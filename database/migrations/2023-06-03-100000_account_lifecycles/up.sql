@@ -0,0 +1,60 @@
+-- One row per account creation, so the deleted and re-created accounts keep all their lifetimes.
+-- `accounts` table keeps only the latest pair of creation/deletion receipts for compatibility
+CREATE TABLE account_lifecycles
+(
+    id                      bigserial      NOT NULL,
+    account_id              text           NOT NULL,
+    -- NULL for the accounts from genesis and for the accounts created before the indexing start
+    created_by_receipt_id   text,
+    -- NULL for the accounts created before the indexing start
+    -- and for the genesis accounts indexed before this table was introduced
+    created_in_block_height numeric(20, 0),
+    -- NULL while the account exists
+    deleted_by_receipt_id   text,
+    deleted_in_block_height numeric(20, 0)
+);
+
+ALTER TABLE ONLY account_lifecycles
+    ADD CONSTRAINT account_lifecycles_pkey PRIMARY KEY (id);
+
+-- The indexer rewrites all the lifecycles of the account on each its creation or deletion,
+-- so the blocks can be indexed in any order and reindexed.
+-- The account can be created several times in the same block, but each time by its own receipt
+CREATE UNIQUE INDEX account_lifecycles_creation_uidx ON account_lifecycles (created_by_receipt_id);
+CREATE UNIQUE INDEX account_lifecycles_deletion_uidx ON account_lifecycles (deleted_by_receipt_id);
+-- Only the first lifecycle of the account can start before the indexing (or in genesis) and be still open.
+-- The deletions without the known creation are the rows with NULL `created_by_receipt_id` as well,
+-- there can be several of them while the creations are not indexed yet
+CREATE UNIQUE INDEX account_lifecycles_unknown_creation_uidx ON account_lifecycles (account_id)
+    WHERE created_by_receipt_id IS NULL AND deleted_by_receipt_id IS NULL;
+
+CREATE INDEX account_lifecycles_account_id_idx ON account_lifecycles (account_id);
+
+-- The accounts indexed before this migration, one lifecycle per row of `accounts`.
+-- `accounts` keeps only the latest creation and deletion, the earlier lifetimes of the re-created accounts are lost.
+-- This insert is heavy on mainnet
+INSERT INTO account_lifecycles (account_id, created_by_receipt_id, created_in_block_height,
+                                deleted_by_receipt_id, deleted_in_block_height)
+SELECT accounts.account_id,
+       accounts.created_by_receipt_id,
+       creation_blocks.block_height,
+       accounts.deleted_by_receipt_id,
+       deletion_blocks.block_height
+FROM accounts
+         LEFT JOIN execution_outcomes creation_outcomes
+                   ON creation_outcomes.receipt_id = accounts.created_by_receipt_id
+         LEFT JOIN blocks creation_blocks
+                   ON creation_blocks.block_hash = creation_outcomes.executed_in_block_hash
+         LEFT JOIN execution_outcomes deletion_outcomes
+                   ON deletion_outcomes.receipt_id = accounts.deleted_by_receipt_id
+         LEFT JOIN blocks deletion_blocks
+                   ON deletion_blocks.block_hash = deletion_outcomes.executed_in_block_hash;
+
+-- The existing accounts derived from their latest open lifecycles
+CREATE VIEW current_accounts AS
+SELECT DISTINCT ON (account_id) account_id,
+                                created_by_receipt_id,
+                                created_in_block_height
+FROM account_lifecycles
+WHERE deleted_by_receipt_id IS NULL
+ORDER BY account_id, created_in_block_height DESC NULLS LAST;
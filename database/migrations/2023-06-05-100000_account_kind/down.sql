@@ -0,0 +1,7 @@
+ALTER TABLE accounts
+    DROP COLUMN account_kind,
+    DROP COLUMN parent_account_id;
+
+DROP FUNCTION classify_parent_account_id;
+DROP FUNCTION classify_account_kind;
+DROP TYPE account_kind;
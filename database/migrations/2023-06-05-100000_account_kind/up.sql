@@ -0,0 +1,51 @@
+CREATE TYPE account_kind AS ENUM (
+    -- Top-level account without the parent, e.g. `near`
+    'NAMED',
+    -- 64 lowercase hex characters (ed25519 public key)
+    'NEAR_IMPLICIT',
+    -- `0x` followed by 40 lowercase hex characters (Ethereum address)
+    'ETH_IMPLICIT',
+    -- Account with the parent, e.g. `alice.near`
+    'SUB_ACCOUNT'
+    );
+
+ALTER TABLE accounts
+    ADD COLUMN account_kind      account_kind,
+    -- Filled only for SUB_ACCOUNT
+    ADD COLUMN parent_account_id text;
+
+-- The same rules as `models::accounts::classify_account_id`, both are checked against the same fixtures
+-- by the tests in `models/accounts.rs` (the SQL side with `cargo test -- --ignored` and DATABASE_URL set)
+CREATE FUNCTION classify_account_kind(account_id text) RETURNS account_kind
+    LANGUAGE sql
+    IMMUTABLE
+AS
+$$
+SELECT CASE
+           WHEN account_id ~ '^[0-9a-f]{64}$' THEN 'NEAR_IMPLICIT'::account_kind
+           WHEN account_id ~ '^0x[0-9a-f]{40}$' THEN 'ETH_IMPLICIT'::account_kind
+           WHEN strpos(account_id, '.') > 0 THEN 'SUB_ACCOUNT'::account_kind
+           ELSE 'NAMED'::account_kind
+           END
+$$;
+
+-- NULL for all the kinds except SUB_ACCOUNT
+CREATE FUNCTION classify_parent_account_id(account_id text) RETURNS text
+    LANGUAGE sql
+    IMMUTABLE
+AS
+$$
+SELECT CASE
+           WHEN classify_account_kind(account_id) = 'SUB_ACCOUNT'
+               THEN substr(account_id, strpos(account_id, '.') + 1)
+           END
+$$;
+
+-- This update is heavy on mainnet, consider running it in portions by `id` before the migration.
+-- NOT NULL constraint is added by the next migration, so the table is not locked for the whole update
+UPDATE accounts
+SET account_kind      = classify_account_kind(account_id),
+    parent_account_id = classify_parent_account_id(account_id)
+WHERE account_kind IS NULL;
+
+CREATE INDEX accounts_parent_account_id_idx ON accounts (parent_account_id);
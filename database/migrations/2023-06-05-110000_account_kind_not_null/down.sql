@@ -0,0 +1,2 @@
+ALTER TABLE accounts
+    ALTER COLUMN account_kind DROP NOT NULL;
@@ -0,0 +1,3 @@
+-- `account_kind` is filled for all the accounts by the previous migration
+ALTER TABLE accounts
+    ALTER COLUMN account_kind SET NOT NULL;
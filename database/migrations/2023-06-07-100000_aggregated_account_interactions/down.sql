@@ -0,0 +1,3 @@
+DROP FUNCTION aggregated__rebuild_account_interactions(numeric, numeric);
+DROP TABLE aggregated__processed_blocks;
+DROP TABLE aggregated__account_interactions;
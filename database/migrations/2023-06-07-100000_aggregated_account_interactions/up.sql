@@ -0,0 +1,100 @@
+-- Who interacts with whom: the actions of the executed receipts
+-- aggregated by predecessor, receiver, UTC day of the execution and action kind.
+-- The actions inside DELEGATE_ACTION are not counted separately
+CREATE TABLE aggregated__account_interactions
+(
+    predecessor_account_id text           NOT NULL,
+    receiver_account_id    text           NOT NULL,
+    -- The start of the UTC day in nanoseconds
+    day_timestamp          numeric(20, 0) NOT NULL,
+    action_kind            action_kind    NOT NULL,
+    actions_count          bigint         NOT NULL,
+    -- Deposits of TRANSFER and FUNCTION_CALL actions
+    total_deposit          numeric(45, 0) NOT NULL,
+    -- Gas burnt by the receipts with the actions of this kind.
+    -- The receipt with several action kinds is accounted in each of them
+    total_gas_burnt        numeric(45, 0) NOT NULL
+);
+
+ALTER TABLE ONLY aggregated__account_interactions
+    ADD CONSTRAINT aggregated__account_interactions_pkey
+        PRIMARY KEY (predecessor_account_id, receiver_account_id, day_timestamp, action_kind);
+
+CREATE INDEX aggregated__account_interactions_receiver_idx
+    ON aggregated__account_interactions (receiver_account_id, day_timestamp);
+CREATE INDEX aggregated__account_interactions_day_idx
+    ON aggregated__account_interactions (day_timestamp);
+
+-- The blocks already added to the incremental aggregations,
+-- so the blocks indexed twice (e.g. after the restart) are not counted twice
+CREATE TABLE aggregated__processed_blocks
+(
+    aggregation  text           NOT NULL,
+    block_height numeric(20, 0) NOT NULL
+);
+
+ALTER TABLE ONLY aggregated__processed_blocks
+    ADD CONSTRAINT aggregated__processed_blocks_pkey PRIMARY KEY (aggregation, block_height);
+
+-- Full rebuild of the given UTC days [from_day_timestamp, to_day_timestamp] from the indexed tables.
+-- Returns the number of the stored rows
+CREATE FUNCTION aggregated__rebuild_account_interactions(from_day_timestamp numeric, to_day_timestamp numeric)
+    RETURNS integer
+    LANGUAGE plpgsql
+AS
+$$
+DECLARE
+    day_nanoseconds CONSTANT numeric := 86400000000000;
+    from_timestamp           numeric := from_day_timestamp - mod(from_day_timestamp, day_nanoseconds);
+    until_timestamp          numeric := to_day_timestamp - mod(to_day_timestamp, day_nanoseconds) + day_nanoseconds;
+    stored_rows_count        integer;
+BEGIN
+    DELETE
+    FROM aggregated__account_interactions
+    WHERE day_timestamp >= from_timestamp
+      AND day_timestamp < until_timestamp;
+
+    INSERT INTO aggregated__account_interactions
+    SELECT receipt_actions.receipt_predecessor_account_id,
+           receipt_actions.receipt_receiver_account_id,
+           receipt_actions.day_timestamp,
+           receipt_actions.action_kind,
+           sum(receipt_actions.actions_count),
+           sum(receipt_actions.total_deposit),
+           sum(receipt_actions.gas_burnt)
+    FROM (SELECT action_receipt_actions.receipt_predecessor_account_id,
+                 action_receipt_actions.receipt_receiver_account_id,
+                 execution_outcomes.executed_in_block_timestamp -
+                 mod(execution_outcomes.executed_in_block_timestamp, day_nanoseconds) AS day_timestamp,
+                 action_receipt_actions.action_kind,
+                 count(*)                                                            AS actions_count,
+                 coalesce(sum((action_receipt_actions.args ->> 'deposit')::numeric), 0) AS total_deposit,
+                 -- The same for all the actions of the receipt
+                 min(execution_outcomes.gas_burnt)                                   AS gas_burnt
+          FROM action_receipt_actions
+                   JOIN execution_outcomes ON execution_outcomes.receipt_id = action_receipt_actions.receipt_id
+          WHERE execution_outcomes.executed_in_block_timestamp >= from_timestamp
+            AND execution_outcomes.executed_in_block_timestamp < until_timestamp
+            AND action_receipt_actions.delegate_parent_index_in_action_receipt IS NULL
+          GROUP BY action_receipt_actions.receipt_id,
+                   action_receipt_actions.receipt_predecessor_account_id,
+                   action_receipt_actions.receipt_receiver_account_id,
+                   day_timestamp,
+                   action_receipt_actions.action_kind) AS receipt_actions
+    GROUP BY receipt_actions.receipt_predecessor_account_id,
+             receipt_actions.receipt_receiver_account_id,
+             receipt_actions.day_timestamp,
+             receipt_actions.action_kind;
+    GET DIAGNOSTICS stored_rows_count = ROW_COUNT;
+
+    -- The blocks of the rebuilt days are already counted
+    INSERT INTO aggregated__processed_blocks
+    SELECT 'account_interactions', block_height
+    FROM blocks
+    WHERE block_timestamp >= from_timestamp
+      AND block_timestamp < until_timestamp
+    ON CONFLICT DO NOTHING;
+
+    RETURN stored_rows_count;
+END;
+$$;
@@ -0,0 +1,4 @@
+DROP FUNCTION aggregated__compute_daily_stats(numeric);
+DROP INDEX account_lifecycles_deleted_in_block_height_idx;
+DROP INDEX account_lifecycles_created_in_block_height_idx;
+DROP TABLE aggregated__daily_stats;
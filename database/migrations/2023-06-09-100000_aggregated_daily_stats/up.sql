@@ -0,0 +1,92 @@
+-- Network statistics for each UTC day
+CREATE TABLE aggregated__daily_stats
+(
+    -- The start of the UTC day in nanoseconds
+    day_timestamp            numeric(20, 0) NOT NULL,
+    transactions_count       bigint         NOT NULL,
+    -- Distinct signers of the transactions
+    active_accounts_count    bigint         NOT NULL,
+    -- The accounts from genesis are not counted. `account_lifecycles` is filled from `accounts`
+    -- by its migration, so the days indexed before it are covered as well
+    new_accounts_count       bigint         NOT NULL,
+    deleted_accounts_count   bigint         NOT NULL,
+    gas_used                 numeric(45, 0) NOT NULL,
+    -- Burnt for the conversion of the transactions to receipts and for the execution of the receipts
+    tokens_burnt             numeric(45, 0) NOT NULL,
+    deployed_contracts_count bigint         NOT NULL
+);
+
+ALTER TABLE ONLY aggregated__daily_stats
+    ADD CONSTRAINT aggregated__daily_stats_pkey PRIMARY KEY (day_timestamp);
+
+CREATE INDEX account_lifecycles_created_in_block_height_idx ON account_lifecycles (created_in_block_height);
+CREATE INDEX account_lifecycles_deleted_in_block_height_idx ON account_lifecycles (deleted_in_block_height);
+
+-- Computes the stats of the UTC day containing the given timestamp from the indexed tables
+-- and stores them, replacing the previous values.
+-- Returns the number of the stored rows, 0 if there are no indexed blocks within the day
+CREATE FUNCTION aggregated__compute_daily_stats(day_timestamp numeric)
+    RETURNS integer
+    LANGUAGE plpgsql
+AS
+$$
+DECLARE
+    day_nanoseconds CONSTANT numeric := 86400000000000;
+    from_timestamp           numeric := day_timestamp - mod(day_timestamp, day_nanoseconds);
+    until_timestamp          numeric := from_timestamp + day_nanoseconds;
+    from_block_height        numeric;
+    to_block_height          numeric;
+BEGIN
+    SELECT min(block_height), max(block_height)
+    INTO from_block_height, to_block_height
+    FROM blocks
+    WHERE block_timestamp >= from_timestamp
+      AND block_timestamp < until_timestamp;
+
+    IF from_block_height IS NULL THEN
+        RETURN 0;
+    END IF;
+
+    INSERT INTO aggregated__daily_stats
+    SELECT from_timestamp,
+           transactions_stats.transactions_count,
+           transactions_stats.active_accounts_count,
+           (SELECT count(*)
+            FROM account_lifecycles
+            WHERE created_in_block_height BETWEEN from_block_height AND to_block_height
+              AND created_by_receipt_id IS NOT NULL),
+           (SELECT count(*)
+            FROM account_lifecycles
+            WHERE deleted_in_block_height BETWEEN from_block_height AND to_block_height),
+           (SELECT coalesce(sum(chunks.gas_used), 0)
+            FROM chunks
+                     JOIN blocks ON blocks.block_hash = chunks.included_in_block_hash
+            WHERE blocks.block_timestamp >= from_timestamp
+              AND blocks.block_timestamp < until_timestamp),
+           transactions_stats.conversion_tokens_burnt +
+           (SELECT coalesce(sum(tokens_burnt), 0)
+            FROM execution_outcomes
+            WHERE executed_in_block_timestamp >= from_timestamp
+              AND executed_in_block_timestamp < until_timestamp),
+           (SELECT count(*)
+            FROM contract_deployments
+            WHERE deployed_in_block_timestamp >= from_timestamp
+              AND deployed_in_block_timestamp < until_timestamp)
+    FROM (SELECT count(*)                                             AS transactions_count,
+                 count(DISTINCT signer_account_id)                    AS active_accounts_count,
+                 coalesce(sum(receipt_conversion_tokens_burnt), 0)    AS conversion_tokens_burnt
+          FROM transactions
+          WHERE block_timestamp >= from_timestamp
+            AND block_timestamp < until_timestamp) AS transactions_stats
+    ON CONFLICT (day_timestamp) DO UPDATE
+        SET transactions_count       = excluded.transactions_count,
+            active_accounts_count    = excluded.active_accounts_count,
+            new_accounts_count       = excluded.new_accounts_count,
+            deleted_accounts_count   = excluded.deleted_accounts_count,
+            gas_used                 = excluded.gas_used,
+            tokens_burnt             = excluded.tokens_burnt,
+            deployed_contracts_count = excluded.deployed_contracts_count;
+
+    RETURN 1;
+END;
+$$;
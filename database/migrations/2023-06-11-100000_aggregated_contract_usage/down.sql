@@ -0,0 +1,3 @@
+DROP FUNCTION aggregated__rebuild_contract_usage(numeric, numeric);
+DROP TABLE aggregated__contract_usage_callers;
+DROP TABLE aggregated__contract_usage;
@@ -0,0 +1,109 @@
+-- FUNCTION_CALL actions of the executed receipts
+-- aggregated by receiver contract, UTC day of the execution and method name.
+-- The actions inside DELEGATE_ACTION are not counted separately
+CREATE TABLE aggregated__contract_usage
+(
+    contract_account_id  text           NOT NULL,
+    -- The start of the UTC day in nanoseconds
+    day_timestamp        numeric(20, 0) NOT NULL,
+    method_name          text           NOT NULL,
+    calls_count          bigint         NOT NULL,
+    -- Distinct predecessors of the calls
+    unique_callers_count bigint         NOT NULL,
+    -- Gas burnt by the receipts with the calls of this method.
+    -- The receipt calling several methods is accounted in each of them
+    total_gas_burnt      numeric(45, 0) NOT NULL,
+    total_deposit        numeric(45, 0) NOT NULL
+);
+
+ALTER TABLE ONLY aggregated__contract_usage
+    ADD CONSTRAINT aggregated__contract_usage_pkey PRIMARY KEY (contract_account_id, day_timestamp, method_name);
+
+CREATE INDEX aggregated__contract_usage_day_idx ON aggregated__contract_usage (day_timestamp);
+
+-- The callers already counted in `aggregated__contract_usage.unique_callers_count`
+CREATE TABLE aggregated__contract_usage_callers
+(
+    contract_account_id text           NOT NULL,
+    day_timestamp       numeric(20, 0) NOT NULL,
+    method_name         text           NOT NULL,
+    caller_account_id   text           NOT NULL
+);
+
+ALTER TABLE ONLY aggregated__contract_usage_callers
+    ADD CONSTRAINT aggregated__contract_usage_callers_pkey
+        PRIMARY KEY (contract_account_id, day_timestamp, method_name, caller_account_id);
+
+CREATE INDEX aggregated__contract_usage_callers_day_idx ON aggregated__contract_usage_callers (day_timestamp);
+
+-- Full rebuild of the given UTC days [from_day_timestamp, to_day_timestamp] from the indexed tables.
+-- Returns the number of the stored rows of `aggregated__contract_usage`
+CREATE FUNCTION aggregated__rebuild_contract_usage(from_day_timestamp numeric, to_day_timestamp numeric)
+    RETURNS integer
+    LANGUAGE plpgsql
+AS
+$$
+DECLARE
+    day_nanoseconds CONSTANT numeric := 86400000000000;
+    from_timestamp           numeric := from_day_timestamp - mod(from_day_timestamp, day_nanoseconds);
+    until_timestamp          numeric := to_day_timestamp - mod(to_day_timestamp, day_nanoseconds) + day_nanoseconds;
+    stored_rows_count        integer;
+BEGIN
+    DELETE
+    FROM aggregated__contract_usage
+    WHERE day_timestamp >= from_timestamp
+      AND day_timestamp < until_timestamp;
+    DELETE
+    FROM aggregated__contract_usage_callers
+    WHERE day_timestamp >= from_timestamp
+      AND day_timestamp < until_timestamp;
+
+    WITH receipt_calls AS (SELECT action_receipt_actions.receipt_receiver_account_id                  AS contract_account_id,
+                                  execution_outcomes.executed_in_block_timestamp -
+                                  mod(execution_outcomes.executed_in_block_timestamp, day_nanoseconds) AS day_timestamp,
+                                  action_receipt_actions.args ->> 'method_name'                       AS method_name,
+                                  action_receipt_actions.receipt_predecessor_account_id               AS caller_account_id,
+                                  count(*)                                                            AS calls_count,
+                                  -- The same for all the actions of the receipt
+                                  min(execution_outcomes.gas_burnt)                                   AS gas_burnt,
+                                  sum((action_receipt_actions.args ->> 'deposit')::numeric)           AS total_deposit
+                           FROM action_receipt_actions
+                                    JOIN execution_outcomes
+                                         ON execution_outcomes.receipt_id = action_receipt_actions.receipt_id
+                           WHERE execution_outcomes.executed_in_block_timestamp >= from_timestamp
+                             AND execution_outcomes.executed_in_block_timestamp < until_timestamp
+                             AND action_receipt_actions.action_kind = 'FUNCTION_CALL'
+                             AND action_receipt_actions.delegate_parent_index_in_action_receipt IS NULL
+                           GROUP BY action_receipt_actions.receipt_id,
+                                    contract_account_id,
+                                    day_timestamp,
+                                    method_name,
+                                    caller_account_id),
+         stored_callers AS (
+             INSERT INTO aggregated__contract_usage_callers
+                 SELECT DISTINCT contract_account_id, day_timestamp, method_name, caller_account_id
+                 FROM receipt_calls)
+    INSERT
+    INTO aggregated__contract_usage
+    SELECT contract_account_id,
+           day_timestamp,
+           method_name,
+           sum(calls_count),
+           count(DISTINCT caller_account_id),
+           sum(gas_burnt),
+           sum(total_deposit)
+    FROM receipt_calls
+    GROUP BY contract_account_id, day_timestamp, method_name;
+    GET DIAGNOSTICS stored_rows_count = ROW_COUNT;
+
+    -- The blocks of the rebuilt days are already counted
+    INSERT INTO aggregated__processed_blocks
+    SELECT 'contract_usage', block_height
+    FROM blocks
+    WHERE block_timestamp >= from_timestamp
+      AND block_timestamp < until_timestamp
+    ON CONFLICT DO NOTHING;
+
+    RETURN stored_rows_count;
+END;
+$$;
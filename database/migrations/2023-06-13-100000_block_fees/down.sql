@@ -0,0 +1,2 @@
+DROP VIEW epoch_fees;
+DROP TABLE block_fees;
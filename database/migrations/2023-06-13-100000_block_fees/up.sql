@@ -0,0 +1,45 @@
+-- The fees of the transactions and receipts executed in the block
+CREATE TABLE block_fees
+(
+    block_height              numeric(20, 0) NOT NULL,
+    block_hash                text           NOT NULL,
+    block_timestamp           numeric(20, 0) NOT NULL,
+    -- The same as `blocks.gas_price`. The chunks of the block are applied with the gas price of the previous block
+    gas_price                 numeric(45, 0) NOT NULL,
+    -- The sum of `gas_used` of the chunks included in the block
+    gas_used                  numeric(20, 0) NOT NULL,
+    -- The sum of `transactions.receipt_conversion_tokens_burnt`
+    transactions_tokens_burnt numeric(45, 0) NOT NULL,
+    -- The sum of `execution_outcomes.tokens_burnt`
+    receipts_tokens_burnt     numeric(45, 0) NOT NULL,
+    tokens_burnt              numeric(45, 0) NOT NULL,
+    -- The part of `receipts_tokens_burnt` given to the called contracts instead of burning. It is computed from
+    -- the contract execution gas (`WASM_HOST_COST` of the gas profiles) and the gas price of the outcomes
+    contract_reward           numeric(45, 0) NOT NULL
+);
+
+ALTER TABLE ONLY block_fees
+    ADD CONSTRAINT block_fees_pkey PRIMARY KEY (block_height);
+
+ALTER TABLE ONLY block_fees
+    ADD CONSTRAINT block_fees_block_hash_fk FOREIGN KEY (block_hash) REFERENCES blocks (block_hash) ON DELETE CASCADE;
+
+CREATE INDEX block_fees_block_timestamp_idx ON block_fees (block_timestamp);
+
+-- The fees of the blocks of each epoch. Only the epochs stored to `epochs` table with known boundaries are listed
+CREATE VIEW epoch_fees AS
+SELECT epochs.epoch_id,
+       epochs.epoch_height,
+       epochs.start_block_height,
+       epochs.end_block_height,
+       count(block_fees.block_height)                AS blocks_count,
+       min(block_fees.gas_price)                     AS min_gas_price,
+       max(block_fees.gas_price)                     AS max_gas_price,
+       coalesce(sum(block_fees.gas_used), 0)         AS gas_used,
+       coalesce(sum(block_fees.tokens_burnt), 0)     AS tokens_burnt,
+       coalesce(sum(block_fees.contract_reward), 0)  AS contract_reward
+FROM epochs
+         LEFT JOIN block_fees ON block_fees.block_height BETWEEN epochs.start_block_height AND epochs.end_block_height
+WHERE epochs.start_block_height IS NOT NULL
+  AND epochs.end_block_height IS NOT NULL
+GROUP BY epochs.epoch_id;
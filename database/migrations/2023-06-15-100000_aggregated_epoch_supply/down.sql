@@ -0,0 +1,2 @@
+DROP FUNCTION aggregated__compute_epoch_supply(numeric);
+DROP TABLE aggregated__epoch_supply;
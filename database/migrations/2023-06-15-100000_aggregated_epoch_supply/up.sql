@@ -0,0 +1,69 @@
+-- The change of the total supply within each epoch:
+-- total_supply_after = total_supply_before + minted_tokens - burnt_tokens
+CREATE TABLE aggregated__epoch_supply
+(
+    epoch_id              text           NOT NULL,
+    epoch_height          numeric(20, 0),
+    start_block_height    numeric(20, 0) NOT NULL,
+    start_block_timestamp numeric(20, 0) NOT NULL,
+    end_block_height      numeric(20, 0) NOT NULL,
+    end_block_timestamp   numeric(20, 0) NOT NULL,
+    -- The total supply of the last block of the previous epoch
+    total_supply_before   numeric(45, 0) NOT NULL,
+    -- The total supply of the last block of the epoch
+    total_supply_after    numeric(45, 0) NOT NULL,
+    -- Validator rewards (and the protocol treasury part) for the previous epoch,
+    -- they are minted at the first block of the epoch
+    minted_tokens         numeric(45, 0) NOT NULL,
+    -- The sum of `chunks.balance_burnt` of the chunks included in the blocks of the epoch
+    burnt_tokens          numeric(45, 0) NOT NULL
+);
+
+ALTER TABLE ONLY aggregated__epoch_supply
+    ADD CONSTRAINT aggregated__epoch_supply_pkey PRIMARY KEY (epoch_id);
+
+CREATE INDEX aggregated__epoch_supply_end_block_timestamp_idx ON aggregated__epoch_supply (end_block_timestamp);
+
+-- Computes the supply changes of all the epochs finished before until_block_timestamp
+-- which are not computed yet, so the epochs skipped earlier are picked up by the next call.
+-- The epochs are skipped if their boundaries are unknown or `chunks.balance_burnt` is not stored
+-- for their chunks (indexed before it was introduced). The blocks of the epoch are expected to be fully indexed.
+-- Returns the number of the stored rows
+CREATE FUNCTION aggregated__compute_epoch_supply(until_block_timestamp numeric)
+    RETURNS integer
+    LANGUAGE plpgsql
+AS
+$$
+DECLARE
+    stored_rows_count integer;
+BEGIN
+    INSERT INTO aggregated__epoch_supply
+    SELECT epochs.epoch_id,
+           epochs.epoch_height,
+           epochs.start_block_height,
+           start_blocks.block_timestamp,
+           epochs.end_block_height,
+           end_blocks.block_timestamp,
+           previous_blocks.total_supply,
+           end_blocks.total_supply,
+           end_blocks.total_supply - previous_blocks.total_supply + epoch_chunks.burnt_tokens,
+           epoch_chunks.burnt_tokens
+    FROM epochs
+             JOIN blocks AS start_blocks ON start_blocks.block_height = epochs.start_block_height
+             JOIN blocks AS previous_blocks ON previous_blocks.block_hash = start_blocks.prev_block_hash
+             JOIN blocks AS end_blocks ON end_blocks.block_height = epochs.end_block_height
+             CROSS JOIN LATERAL (SELECT coalesce(sum(chunks.balance_burnt), 0) AS burnt_tokens,
+                                        count(chunks.balance_burnt)              AS burnt_known_chunks_count,
+                                        count(*)                                 AS chunks_count
+                                 FROM blocks
+                                          JOIN chunks ON chunks.included_in_block_hash = blocks.block_hash
+                                 WHERE blocks.block_height BETWEEN epochs.start_block_height AND epochs.end_block_height) AS epoch_chunks
+    WHERE end_blocks.block_timestamp < until_block_timestamp
+      AND NOT EXISTS(SELECT 1 FROM aggregated__epoch_supply WHERE aggregated__epoch_supply.epoch_id = epochs.epoch_id)
+      AND epoch_chunks.burnt_known_chunks_count = epoch_chunks.chunks_count
+    ON CONFLICT DO NOTHING;
+    GET DIAGNOSTICS stored_rows_count = ROW_COUNT;
+
+    RETURN stored_rows_count;
+END;
+$$;
@@ -1,11 +1,15 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use actix_diesel::dsl::AsyncRunQueryDsl;
+use bigdecimal::BigDecimal;
 
+use diesel::pg::expression::array_comparison::any;
+use diesel::sql_types::{Array, Nullable, Numeric, Text};
 use diesel::{ExpressionMethods, PgConnection, QueryDsl};
 use futures::try_join;
 use tracing::info;
 
+use crate::adapters::CHUNK_SIZE_FOR_BATCH_INSERT;
 use crate::models;
 use crate::schema;
 
@@ -19,8 +23,38 @@ pub async fn handle_access_keys(
     }
 
     let mut access_keys = HashMap::<(String, String), models::access_keys::AccessKey>::new();
+    let mut access_key_history: Vec<models::access_keys::AccessKeyHistory> = vec![];
+    // The order of the key changes made by the receipt, it can delete and re-add the same key
+    let mut receipt_change_indices = HashMap::<near_indexer_primitives::CryptoHash, i32>::new();
+    // The permissions of the existing keys, `None` for the deleted ones
+    let mut permissions = get_stored_permissions(pool, state_changes, block_height).await?;
+    // Nonces (and allowances of FunctionCall keys) are changed by the transactions signed with the keys
+    let mut nonces = HashMap::<(String, String), (BigDecimal, Option<BigDecimal>)>::new();
 
     for state_change in state_changes {
+        if let near_indexer_primitives::views::StateChangeCauseView::TransactionProcessing {
+            ..
+        } = state_change.cause
+        {
+            if let near_indexer_primitives::views::StateChangeValueView::AccessKeyUpdate {
+                account_id,
+                public_key,
+                access_key,
+            } = &state_change.value
+            {
+                let allowance = match &access_key.permission {
+                    near_indexer_primitives::views::AccessKeyPermissionView::FunctionCall {
+                        allowance,
+                        ..
+                    } => allowance.map(models::u128_to_big_decimal),
+                    near_indexer_primitives::views::AccessKeyPermissionView::FullAccess => None,
+                };
+                nonces.insert(
+                    (public_key.to_string(), account_id.to_string()),
+                    (access_key.nonce.into(), allowance),
+                );
+            }
+        }
         if let near_indexer_primitives::views::StateChangeCauseView::ReceiptProcessing {
             receipt_hash,
         } = state_change.cause
@@ -31,21 +65,51 @@ pub async fn handle_access_keys(
                     public_key,
                     access_key,
                 } => {
-                    access_keys.insert(
-                        (public_key.to_string(), account_id.to_string()),
-                        models::access_keys::AccessKey::from_action_view(
-                            public_key,
-                            account_id,
-                            access_key,
-                            &receipt_hash,
-                            block_height,
-                        ),
+                    let access_key = models::access_keys::AccessKey::from_action_view(
+                        public_key,
+                        account_id,
+                        access_key,
+                        &receipt_hash,
+                        block_height,
                     );
+                    let permission = Permission::from(&access_key);
+                    let action = match permissions.insert(
+                        (public_key.to_string(), account_id.to_string()),
+                        Some(permission.clone()),
+                    ) {
+                        Some(Some(previous_permission)) if previous_permission == permission => {
+                            // The nonce of the existing key is updated, e.g. by the delegate action
+                            None
+                        }
+                        Some(Some(_)) => {
+                            Some(models::enums::AccessKeyHistoryAction::PermissionChange)
+                        }
+                        Some(None) | None => Some(models::enums::AccessKeyHistoryAction::Add),
+                    };
+                    if let Some(action) = action {
+                        access_key_history.extend(
+                            models::access_keys::AccessKeyHistory::from_access_key(
+                                &access_key,
+                                action,
+                                next_change_index(&mut receipt_change_indices, receipt_hash),
+                            ),
+                        );
+                    }
+                    access_keys
+                        .insert((public_key.to_string(), account_id.to_string()), access_key);
                 }
                 near_indexer_primitives::views::StateChangeValueView::AccessKeyDeletion {
                     account_id,
                     public_key,
                 } => {
+                    permissions.insert((public_key.to_string(), account_id.to_string()), None);
+                    access_key_history.push(models::access_keys::AccessKeyHistory::new_deleted(
+                        public_key,
+                        account_id,
+                        &receipt_hash,
+                        next_change_index(&mut receipt_change_indices, receipt_hash),
+                        block_height,
+                    ));
                     access_keys
                         .entry((public_key.to_string(), account_id.to_string()))
                         .and_modify(|existing_access_key| {
@@ -61,6 +125,10 @@ pub async fn handle_access_keys(
                             // permission_kind is not supposed to change on delete action
                             permission_kind: models::enums::AccessKeyPermission::FullAccess,
                             last_update_block_height: block_height.into(),
+                            permission_allowance: None,
+                            permission_receiver_id: None,
+                            permission_method_names: None,
+                            nonce: None,
                         });
                 }
                 _ => continue,
@@ -68,6 +136,20 @@ pub async fn handle_access_keys(
         }
     }
 
+    // The history is append-only, `access_keys` is the latest state of each key from it
+    for chunk in access_key_history.chunks(CHUNK_SIZE_FOR_BATCH_INSERT) {
+        let access_key_history_chunk = chunk.to_owned();
+        crate::await_retry_or_panic!(
+            diesel::insert_into(schema::access_key_history::table)
+                .values(access_key_history_chunk.clone())
+                .on_conflict_do_nothing()
+                .execute_async(pool),
+            10,
+            "AccessKeyHistory was stored in database".to_string(),
+            &access_key_history_chunk
+        );
+    }
+
     let (access_keys_to_insert, access_keys_to_update): (
         Vec<models::access_keys::AccessKey>,
         Vec<models::access_keys::AccessKey>,
@@ -130,6 +212,14 @@ pub async fn handle_access_keys(
                             .eq(value.created_by_receipt_id.clone()),
                         schema::access_keys::dsl::deleted_by_receipt_id
                             .eq(value.deleted_by_receipt_id.clone()),
+                        schema::access_keys::dsl::permission_kind.eq(value.permission_kind.clone()),
+                        schema::access_keys::dsl::permission_allowance
+                            .eq(value.permission_allowance.clone()),
+                        schema::access_keys::dsl::permission_receiver_id
+                            .eq(value.permission_receiver_id.clone()),
+                        schema::access_keys::dsl::permission_method_names
+                            .eq(value.permission_method_names.clone()),
+                        schema::access_keys::dsl::nonce.eq(value.nonce.clone()),
                         schema::access_keys::dsl::last_update_block_height
                             .eq(value.last_update_block_height.clone()),
                     ))
@@ -144,9 +234,149 @@ pub async fn handle_access_keys(
 
     try_join!(update_access_keys_future, add_access_keys_future)?;
 
+    // The nonce only grows, so we don't rely on `last_update_block_height` here.
+    // The re-added key starts with the greater nonce as well
+    let nonces: Vec<_> = nonces.into_iter().collect();
+    for chunk in nonces.chunks(CHUNK_SIZE_FOR_BATCH_INSERT) {
+        let (keys, values): (Vec<_>, Vec<_>) = chunk.iter().cloned().unzip();
+        let (public_keys, account_ids): (Vec<String>, Vec<String>) = keys.into_iter().unzip();
+        let (key_nonces, allowances): (Vec<BigDecimal>, Vec<Option<BigDecimal>>) =
+            values.into_iter().unzip();
+
+        crate::await_retry_or_panic!(
+            diesel::sql_query(
+                r"
+                UPDATE access_keys
+                SET nonce = updates.nonce,
+                    permission_allowance = updates.permission_allowance
+                FROM unnest($1, $2, $3, $4)
+                    AS updates(public_key, account_id, nonce, permission_allowance)
+                WHERE access_keys.public_key = updates.public_key
+                    AND access_keys.account_id = updates.account_id
+                    AND (access_keys.nonce IS NULL OR access_keys.nonce < updates.nonce)
+                ",
+            )
+            .bind::<Array<Text>, _>(public_keys.clone())
+            .bind::<Array<Text>, _>(account_ids.clone())
+            .bind::<Array<Numeric>, _>(key_nonces.clone())
+            .bind::<Array<Nullable<Numeric>>, _>(allowances.clone())
+            .execute_async(pool),
+            10,
+            "AccessKey nonces were updated".to_string(),
+            &public_keys
+        );
+    }
+
     Ok(())
 }
 
+/// The permission of the key as far as the history is concerned.
+/// The allowance is spent by the transactions, so it's not a part of it
+#[derive(Clone, PartialEq, Eq)]
+struct Permission {
+    kind: models::enums::AccessKeyPermission,
+    receiver_id: Option<String>,
+    method_names: Option<Vec<String>>,
+}
+
+impl From<&models::access_keys::AccessKey> for Permission {
+    fn from(access_key: &models::access_keys::AccessKey) -> Self {
+        Self {
+            kind: access_key.permission_kind.clone(),
+            receiver_id: access_key.permission_receiver_id.clone(),
+            method_names: access_key.permission_method_names.clone(),
+        }
+    }
+}
+
+fn next_change_index(
+    receipt_change_indices: &mut HashMap<near_indexer_primitives::CryptoHash, i32>,
+    receipt_id: near_indexer_primitives::CryptoHash,
+) -> i32 {
+    let index = receipt_change_indices.entry(receipt_id).or_insert(0);
+    *index += 1;
+    *index - 1
+}
+
+/// The permissions of the stored keys the receipts of the block add, as they were before the block
+async fn get_stored_permissions(
+    pool: &actix_diesel::Database<PgConnection>,
+    state_changes: &[near_indexer_primitives::views::StateChangeWithCauseView],
+    block_height: near_indexer_primitives::types::BlockHeight,
+) -> anyhow::Result<HashMap<(String, String), Option<Permission>>> {
+    let added_keys: HashSet<(String, String)> = state_changes
+        .iter()
+        .filter_map(
+            |state_change| match (&state_change.cause, &state_change.value) {
+                (
+                    near_indexer_primitives::views::StateChangeCauseView::ReceiptProcessing {
+                        ..
+                    },
+                    near_indexer_primitives::views::StateChangeValueView::AccessKeyUpdate {
+                        account_id,
+                        public_key,
+                        ..
+                    },
+                ) => Some((public_key.to_string(), account_id.to_string())),
+                _ => None,
+            },
+        )
+        .collect();
+    if added_keys.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let (public_keys, account_ids): (Vec<String>, Vec<String>) = added_keys.iter().cloned().unzip();
+
+    let stored_keys: Vec<(
+        String,
+        String,
+        Option<String>,
+        models::enums::AccessKeyPermission,
+        Option<String>,
+        Option<Vec<String>>,
+    )> = crate::await_retry_or_panic!(
+        schema::access_keys::table
+            .select((
+                schema::access_keys::dsl::public_key,
+                schema::access_keys::dsl::account_id,
+                schema::access_keys::dsl::deleted_by_receipt_id,
+                schema::access_keys::dsl::permission_kind,
+                schema::access_keys::dsl::permission_receiver_id,
+                schema::access_keys::dsl::permission_method_names,
+            ))
+            .filter(schema::access_keys::dsl::public_key.eq(any(public_keys.clone())))
+            .filter(schema::access_keys::dsl::account_id.eq(any(account_ids)))
+            .filter(
+                schema::access_keys::dsl::last_update_block_height
+                    .lt(BigDecimal::from(block_height)),
+            )
+            .load_async(pool),
+        10,
+        "Stored AccessKeys permissions were fetched".to_string(),
+        &public_keys
+    )
+    .unwrap_or_default();
+
+    Ok(stored_keys
+        .into_iter()
+        .map(
+            |(public_key, account_id, deleted_by_receipt_id, kind, receiver_id, method_names)| {
+                let permission = Permission {
+                    kind,
+                    receiver_id,
+                    method_names,
+                };
+                let permission = match deleted_by_receipt_id {
+                    Some(_) => None,
+                    None => Some(permission),
+                };
+                ((public_key, account_id), permission)
+            },
+        )
+        .filter(|(key, _)| added_keys.contains(key))
+        .collect())
+}
+
 pub(crate) async fn store_access_keys_from_genesis(
     pool: actix_diesel::Database<PgConnection>,
     access_keys_models: Vec<models::access_keys::AccessKey>,
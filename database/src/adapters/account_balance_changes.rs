@@ -0,0 +1,170 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use actix_diesel::dsl::AsyncRunQueryDsl;
+use bigdecimal::BigDecimal;
+use diesel::sql_types::{Array, Numeric, Text};
+use diesel::PgConnection;
+
+use crate::adapters::CHUNK_SIZE_FOR_BATCH_INSERT;
+use crate::models;
+use crate::schema;
+
+/// `account_balance_changes` is partitioned by 10M blocks, see the migration
+const PARTITION_SIZE: u64 = 10_000_000;
+
+/// The partition (block height / `PARTITION_SIZE`) the partitions were prepared for
+static PREPARED_PARTITION: AtomicU64 = AtomicU64::new(u64::MAX);
+
+/// Saves the changes of the native balances and the storage usage of the accounts.
+/// The updates of the account caused by the same receipt (or transaction) in a row
+/// are collapsed into the last one, the updates without the actual change
+/// (compared to the previous update in the block or the last stored one) are skipped
+pub async fn store_account_balance_changes(
+    pool: &actix_diesel::Database<PgConnection>,
+    shards: &[near_indexer_primitives::IndexerShard],
+    block_height: near_indexer_primitives::types::BlockHeight,
+    block_timestamp: u64,
+) -> anyhow::Result<()> {
+    let changes: Vec<models::account_balance_changes::AccountBalanceChange> = shards
+        .iter()
+        .flat_map(|shard| shard.state_changes.iter())
+        .filter_map(|state_change| {
+            models::account_balance_changes::AccountBalanceChange::from_state_change_with_cause(
+                state_change,
+                block_height,
+                block_timestamp,
+            )
+        })
+        .collect();
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    prepare_partitions(pool, block_height).await?;
+
+    let account_ids: Vec<String> = changes
+        .iter()
+        .map(|change| change.account_id.clone())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let last_stored_changes = get_last_stored_changes(pool, account_ids, block_height).await?;
+    let account_balance_change_models =
+        compose_account_balance_changes(changes.into_iter(), last_stored_changes);
+
+    for chunk in account_balance_change_models.chunks(CHUNK_SIZE_FOR_BATCH_INSERT) {
+        let account_balance_changes_chunk = chunk.to_owned();
+        crate::await_retry_or_panic!(
+            diesel::insert_into(schema::account_balance_changes::table)
+                .values(account_balance_changes_chunk.clone())
+                .on_conflict_do_nothing()
+                .execute_async(pool),
+            10,
+            "AccountBalanceChanges were stored to database".to_string(),
+            &account_balance_changes_chunk
+        );
+    }
+    Ok(())
+}
+
+/// Creates the partition of the given block and the next one if they don't exist yet.
+/// It's done once per partition, the next partition is ready long before the indexer reaches it
+async fn prepare_partitions(
+    pool: &actix_diesel::Database<PgConnection>,
+    block_height: near_indexer_primitives::types::BlockHeight,
+) -> anyhow::Result<()> {
+    let partition = block_height / PARTITION_SIZE;
+    if PREPARED_PARTITION.load(Ordering::Relaxed) == partition {
+        return Ok(());
+    }
+
+    crate::await_retry_or_panic!(
+        diesel::sql_query("SELECT create_account_balance_changes_partitions($1, $2)")
+            .bind::<Numeric, _>(BigDecimal::from(block_height))
+            .bind::<Numeric, _>(BigDecimal::from(block_height + PARTITION_SIZE))
+            .execute_async(pool),
+        10,
+        "AccountBalanceChanges partitions were prepared".to_string(),
+        &block_height
+    );
+    PREPARED_PARTITION.store(partition, Ordering::Relaxed);
+    Ok(())
+}
+
+/// The latest change of each account stored before the given block
+async fn get_last_stored_changes(
+    pool: &actix_diesel::Database<PgConnection>,
+    account_ids: Vec<String>,
+    block_height: near_indexer_primitives::types::BlockHeight,
+) -> anyhow::Result<HashMap<String, models::account_balance_changes::AccountBalanceChange>> {
+    // `DISTINCT ON` would read the whole history of the busy accounts, we need only one line per account
+    let last_stored_changes = crate::await_retry_or_panic!(
+        diesel::sql_query(
+            r"
+            SELECT last_change.*
+            FROM unnest($1) AS accounts(account_id)
+            CROSS JOIN LATERAL (
+                SELECT *
+                FROM account_balance_changes
+                WHERE account_balance_changes.account_id = accounts.account_id
+                    AND account_balance_changes.changed_in_block_height < $2
+                ORDER BY account_balance_changes.changed_in_block_height DESC,
+                    account_balance_changes.index_in_block DESC
+                LIMIT 1
+            ) AS last_change
+            ",
+        )
+        .bind::<Array<Text>, _>(account_ids.clone())
+        .bind::<Numeric, _>(BigDecimal::from(block_height))
+        .load_async::<models::account_balance_changes::AccountBalanceChange>(pool),
+        10,
+        "Last stored AccountBalanceChanges were fetched".to_string(),
+        &account_ids
+    )
+    .unwrap_or_default();
+
+    Ok(last_stored_changes
+        .into_iter()
+        .map(|change| (change.account_id.clone(), change))
+        .collect())
+}
+
+fn compose_account_balance_changes(
+    changes: impl Iterator<Item = models::account_balance_changes::AccountBalanceChange>,
+    last_stored_changes: HashMap<String, models::account_balance_changes::AccountBalanceChange>,
+) -> Vec<models::account_balance_changes::AccountBalanceChange> {
+    let mut collapsed_changes: Vec<models::account_balance_changes::AccountBalanceChange> =
+        Vec::new();
+    let mut last_change_index_by_account: HashMap<String, usize> = HashMap::new();
+    for change in changes {
+        if let Some(&last_change_index) = last_change_index_by_account.get(&change.account_id) {
+            let last_change = &mut collapsed_changes[last_change_index];
+            if last_change.cause_id().is_some() && last_change.cause_id() == change.cause_id() {
+                *last_change = change;
+                continue;
+            }
+        }
+        last_change_index_by_account.insert(change.account_id.clone(), collapsed_changes.len());
+        collapsed_changes.push(change);
+    }
+
+    // Collapsing may leave the neighbour changes of the account with the same balances,
+    // the first change in the block is compared with the last stored one
+    let mut previous_change_by_account = last_stored_changes;
+    let mut account_balance_changes = Vec::new();
+    for change in collapsed_changes {
+        if let Some(previous_change) = previous_change_by_account.get(&change.account_id) {
+            if previous_change.has_same_balances(&change) {
+                continue;
+            }
+        }
+        previous_change_by_account.insert(change.account_id.clone(), change.clone());
+        account_balance_changes.push(change);
+    }
+
+    for (index_in_block, change) in account_balance_changes.iter_mut().enumerate() {
+        change.index_in_block = index_in_block as i32;
+    }
+    account_balance_changes
+}
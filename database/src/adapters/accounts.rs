@@ -1,13 +1,18 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use actix_diesel::dsl::AsyncRunQueryDsl;
 use anyhow::Context;
 use bigdecimal::BigDecimal;
 
-use diesel::{BoolExpressionMethods, ExpressionMethods, PgConnection, QueryDsl};
+use diesel::pg::expression::array_comparison::any;
+use diesel::sql_types::{Array, Text};
+use diesel::{
+    BoolExpressionMethods, Connection, ExpressionMethods, PgConnection, QueryDsl, RunQueryDsl,
+};
 use futures::try_join;
 use tracing::info;
 
+use crate::adapters::CHUNK_SIZE_FOR_BATCH_INSERT;
 use crate::models;
 use crate::schema;
 
@@ -15,6 +20,7 @@ use crate::schema;
 pub async fn handle_accounts(
     pool: &actix_diesel::Database<PgConnection>,
     outcomes: &[near_indexer_primitives::IndexerExecutionOutcomeWithReceipt],
+    state_changes: &[near_indexer_primitives::views::StateChangeWithCauseView],
     block_height: near_indexer_primitives::types::BlockHeight,
 ) -> anyhow::Result<()> {
     if outcomes.is_empty() {
@@ -29,12 +35,13 @@ pub async fn handle_accounts(
                     | near_indexer_primitives::views::ExecutionStatusView::SuccessReceiptId(_)
             )
         })
-        .map(|outcome_with_receipt| &outcome_with_receipt.receipt);
+        .map(|outcome_with_receipt| &outcome_with_receipt.receipt)
+        .collect::<Vec<_>>();
 
     let mut accounts =
         HashMap::<near_indexer_primitives::types::AccountId, models::accounts::Account>::new();
 
-    for receipt in successful_receipts {
+    for receipt in successful_receipts.iter() {
         if let near_indexer_primitives::views::ReceiptEnumView::Action { actions, .. } =
             &receipt.receipt
         {
@@ -51,7 +58,10 @@ pub async fn handle_accounts(
                         );
                     }
                     near_indexer_primitives::views::ActionView::Transfer { .. } => {
-                        if receipt.receiver_id.len() == 64usize {
+                        if models::accounts::classify_account_id(receipt.receiver_id.as_str())
+                            .0
+                            .is_implicit()
+                        {
                             accounts.insert(
                                 receipt.receiver_id.clone(),
                                 models::accounts::Account::new_from_receipt(
@@ -69,11 +79,19 @@ pub async fn handle_accounts(
                                 existing_account.deleted_by_receipt_id =
                                     Some(receipt.receipt_id.to_string())
                             })
-                            .or_insert_with(|| models::accounts::Account {
-                                account_id: receipt.receiver_id.to_string(),
-                                created_by_receipt_id: None,
-                                deleted_by_receipt_id: Some(receipt.receipt_id.to_string()),
-                                last_update_block_height: block_height.into(),
+                            .or_insert_with(|| {
+                                let (account_kind, parent_account_id) =
+                                    models::accounts::classify_account_id(
+                                        receipt.receiver_id.as_str(),
+                                    );
+                                models::accounts::Account {
+                                    account_id: receipt.receiver_id.to_string(),
+                                    created_by_receipt_id: None,
+                                    deleted_by_receipt_id: Some(receipt.receipt_id.to_string()),
+                                    last_update_block_height: block_height.into(),
+                                    account_kind,
+                                    parent_account_id,
+                                }
                             });
                     }
                     _ => {}
@@ -137,7 +155,7 @@ pub async fn handle_accounts(
             Vec<models::accounts::Account>,
             Vec<models::accounts::Account>,
         ) = accounts_to_create_or_update.into_iter().partition(|model| {
-            model.account_id.len() == 64 && model.deleted_by_receipt_id.is_none()
+            model.account_kind.is_implicit() && model.deleted_by_receipt_id.is_none()
         });
 
         for value in implicit_accounts_to_recreate {
@@ -196,6 +214,397 @@ pub async fn handle_accounts(
     // Joining it unless we can't execute it in the correct order
     // see https://github.com/nearprotocol/nearcore/issues/3467
     try_join!(delete_accounts_future, create_or_update_accounts_future)?;
+
+    store_account_lifecycles(pool, &successful_receipts, state_changes, block_height).await
+}
+
+#[derive(Clone, Copy, Debug)]
+enum AccountLifecycleChange {
+    Created,
+    Deleted,
+}
+
+/// The creation or the deletion of the account.
+/// Both fields are `None` for the creation before the indexing start
+#[derive(Clone, Debug, Default)]
+struct LifecycleEvent {
+    receipt_id: Option<String>,
+    block_height: Option<BigDecimal>,
+}
+
+/// The lifecycle changes of the account in one block: the deletion of the account created
+/// in the earlier blocks, the lifecycles started and finished in the block, and the creation
+/// of the account which is still alive at the end of the block
+#[derive(Default)]
+struct BlockLifecycles {
+    leading_deletion: Option<LifecycleEvent>,
+    lifecycles: Vec<models::accounts::AccountLifecycle>,
+    trailing_creation: Option<LifecycleEvent>,
+}
+
+/// Opens a new lifecycle on each account creation and closes it on the deletion.
+/// The blocks are indexed concurrently and can be reindexed, so all the lifecycles
+/// of the changed accounts are composed again from the stored ones and the changes of the block
+async fn store_account_lifecycles(
+    pool: &actix_diesel::Database<PgConnection>,
+    successful_receipts: &[&near_indexer_primitives::views::ReceiptView],
+    state_changes: &[near_indexer_primitives::views::StateChangeWithCauseView],
+    block_height: near_indexer_primitives::types::BlockHeight,
+) -> anyhow::Result<()> {
+    // Each transfer to the implicit account looks like its creation, it is the creation
+    // only if the account got its access key (or the wallet contract) from the receipt
+    let receipts_creating_implicit_accounts: HashSet<(String, String)> = state_changes
+        .iter()
+        .filter_map(
+            |state_change| match (&state_change.cause, &state_change.value) {
+                (
+                    near_indexer_primitives::views::StateChangeCauseView::ReceiptProcessing {
+                        receipt_hash,
+                    },
+                    near_indexer_primitives::views::StateChangeValueView::AccessKeyUpdate {
+                        account_id,
+                        ..
+                    }
+                    | near_indexer_primitives::views::StateChangeValueView::ContractCodeUpdate {
+                        account_id,
+                        ..
+                    },
+                ) => Some((receipt_hash.to_string(), account_id.to_string())),
+                _ => None,
+            },
+        )
+        .collect();
+
+    // The receipts of the account are executed on its shard, so all the changes of the block are here
+    let mut changes_by_account: HashMap<String, Vec<(String, AccountLifecycleChange)>> =
+        HashMap::new();
+    for receipt in successful_receipts {
+        let actions = match &receipt.receipt {
+            near_indexer_primitives::views::ReceiptEnumView::Action { actions, .. } => {
+                actions.as_slice()
+            }
+            near_indexer_primitives::views::ReceiptEnumView::Data { .. } => &[],
+        };
+        let receipt_id = receipt.receipt_id.to_string();
+        let account_id = receipt.receiver_id.to_string();
+        for action in actions {
+            let lifecycle_change = match action {
+                near_indexer_primitives::views::ActionView::CreateAccount => {
+                    AccountLifecycleChange::Created
+                }
+                near_indexer_primitives::views::ActionView::Transfer { .. }
+                    if receipt.predecessor_id != receipt.receiver_id
+                        && models::accounts::classify_account_id(&account_id)
+                            .0
+                            .is_implicit()
+                        && receipts_creating_implicit_accounts
+                            .contains(&(receipt_id.clone(), account_id.clone())) =>
+                {
+                    AccountLifecycleChange::Created
+                }
+                near_indexer_primitives::views::ActionView::DeleteAccount { .. } => {
+                    AccountLifecycleChange::Deleted
+                }
+                _ => continue,
+            };
+            changes_by_account
+                .entry(account_id.clone())
+                .or_default()
+                .push((receipt_id.clone(), lifecycle_change));
+        }
+    }
+    if changes_by_account.is_empty() {
+        return Ok(());
+    }
+
+    let account_ids: Vec<String> = changes_by_account.keys().cloned().collect();
+    crate::await_retry_or_panic!(
+        pool.get({
+            let account_ids = account_ids.clone();
+            let changes_by_account = changes_by_account.clone();
+            move |conn| {
+                conn.transaction::<_, diesel::result::Error, _>(|| {
+                    // The other blocks changing the same accounts wait for this one.
+                    // The locks are taken in the same order to avoid the deadlocks
+                    diesel::sql_query(
+                        r"
+                        SELECT pg_advisory_xact_lock(hashtext(locked.account_id))
+                        FROM unnest($1) AS locked(account_id)
+                        ORDER BY hashtext(locked.account_id)
+                        ",
+                    )
+                    .bind::<Array<Text>, _>(account_ids.clone())
+                    .execute(conn)?;
+
+                    let mut stored_lifecycles_by_account: HashMap<
+                        String,
+                        Vec<models::accounts::AccountLifecycle>,
+                    > = HashMap::new();
+                    for stored_lifecycle in schema::account_lifecycles::table
+                        .select((
+                            schema::account_lifecycles::dsl::account_id,
+                            schema::account_lifecycles::dsl::created_by_receipt_id,
+                            schema::account_lifecycles::dsl::created_in_block_height,
+                            schema::account_lifecycles::dsl::deleted_by_receipt_id,
+                            schema::account_lifecycles::dsl::deleted_in_block_height,
+                        ))
+                        .filter(
+                            schema::account_lifecycles::dsl::account_id
+                                .eq(any(account_ids.clone())),
+                        )
+                        .load::<models::accounts::AccountLifecycle>(conn)?
+                    {
+                        stored_lifecycles_by_account
+                            .entry(stored_lifecycle.account_id.clone())
+                            .or_default()
+                            .push(stored_lifecycle);
+                    }
+
+                    let lifecycles: Vec<models::accounts::AccountLifecycle> = changes_by_account
+                        .iter()
+                        .flat_map(|(account_id, changes)| {
+                            compose_account_lifecycles(
+                                account_id,
+                                stored_lifecycles_by_account
+                                    .remove(account_id)
+                                    .unwrap_or_default(),
+                                changes,
+                                block_height,
+                            )
+                        })
+                        .collect();
+
+                    diesel::delete(
+                        schema::account_lifecycles::table.filter(
+                            schema::account_lifecycles::dsl::account_id.eq(any(account_ids)),
+                        ),
+                    )
+                    .execute(conn)?;
+                    for chunk in lifecycles.chunks(CHUNK_SIZE_FOR_BATCH_INSERT) {
+                        diesel::insert_into(schema::account_lifecycles::table)
+                            .values(chunk)
+                            .execute(conn)?;
+                    }
+                    Ok(())
+                })
+            }
+        }),
+        10,
+        "AccountLifecycles were stored".to_string(),
+        &account_ids
+    );
+
+    Ok(())
+}
+
+/// All the lifecycles of the account: each deletion closes the latest creation before it.
+/// The lifecycles of the given block are replaced with the ones composed from its changes,
+/// so the result does not depend on the order the blocks are indexed in
+fn compose_account_lifecycles(
+    account_id: &str,
+    stored_lifecycles: Vec<models::accounts::AccountLifecycle>,
+    block_changes: &[(String, AccountLifecycleChange)],
+    block_height: near_indexer_primitives::types::BlockHeight,
+) -> Vec<models::accounts::AccountLifecycle> {
+    let block_height = Some(BigDecimal::from(block_height));
+    let lifecycle = |creation: LifecycleEvent, deletion: Option<LifecycleEvent>| {
+        let deletion = deletion.unwrap_or_default();
+        models::accounts::AccountLifecycle {
+            account_id: account_id.to_string(),
+            created_by_receipt_id: creation.receipt_id,
+            created_in_block_height: creation.block_height,
+            deleted_by_receipt_id: deletion.receipt_id,
+            deleted_in_block_height: deletion.block_height,
+        }
+    };
+
+    // `None` height goes first, these are the creations before the indexing start
+    let mut blocks: BTreeMap<Option<BigDecimal>, BlockLifecycles> = BTreeMap::new();
+    for stored_lifecycle in stored_lifecycles {
+        let creation = LifecycleEvent {
+            receipt_id: stored_lifecycle.created_by_receipt_id.clone(),
+            block_height: stored_lifecycle.created_in_block_height.clone(),
+        };
+        let deletion = stored_lifecycle
+            .deleted_by_receipt_id
+            .clone()
+            .map(|receipt_id| LifecycleEvent {
+                receipt_id: Some(receipt_id),
+                block_height: stored_lifecycle.deleted_in_block_height.clone(),
+            });
+        let is_unknown_creation = creation.receipt_id.is_none() && creation.block_height.is_none();
+        match deletion {
+            // The lifecycle within one block, or the one we can't place (indexed without the heights)
+            Some(deletion)
+                if !is_unknown_creation
+                    && (deletion.block_height == creation.block_height
+                        || deletion.block_height.is_none()) =>
+            {
+                if creation.block_height != block_height {
+                    blocks
+                        .entry(creation.block_height.clone())
+                        .or_default()
+                        .lifecycles
+                        .push(stored_lifecycle);
+                }
+            }
+            deletion => {
+                // The unknown creation of the deleted account is derived from the deletion again
+                if !(is_unknown_creation && deletion.is_some())
+                    && creation.block_height != block_height
+                {
+                    let creation_block = blocks.entry(creation.block_height.clone()).or_default();
+                    creation_block.trailing_creation = Some(creation);
+                }
+                if let Some(deletion) = deletion {
+                    if deletion.block_height != block_height {
+                        let deletion_block =
+                            blocks.entry(deletion.block_height.clone()).or_default();
+                        deletion_block.leading_deletion = Some(deletion);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut block_lifecycles = BlockLifecycles::default();
+    for (receipt_id, lifecycle_change) in block_changes {
+        let event = LifecycleEvent {
+            receipt_id: Some(receipt_id.clone()),
+            block_height: block_height.clone(),
+        };
+        match lifecycle_change {
+            AccountLifecycleChange::Created => {
+                if let Some(creation) = block_lifecycles.trailing_creation.replace(event) {
+                    // The deletion between the creations is missing, the lifecycle stays open
+                    block_lifecycles.lifecycles.push(lifecycle(creation, None));
+                }
+            }
+            AccountLifecycleChange::Deleted => match block_lifecycles.trailing_creation.take() {
+                Some(creation) => block_lifecycles
+                    .lifecycles
+                    .push(lifecycle(creation, Some(event))),
+                None if block_lifecycles.leading_deletion.is_none()
+                    && block_lifecycles.lifecycles.is_empty() =>
+                {
+                    block_lifecycles.leading_deletion = Some(event)
+                }
+                None => block_lifecycles
+                    .lifecycles
+                    .push(lifecycle(LifecycleEvent::default(), Some(event))),
+            },
+        }
+    }
+    blocks.insert(block_height, block_lifecycles);
+
+    let mut lifecycles = vec![];
+    let mut open_creation: Option<LifecycleEvent> = None;
+    for block in blocks.into_values() {
+        if let Some(deletion) = block.leading_deletion {
+            // The account without the known creation was created before the indexing start,
+            // or its creation is not indexed yet
+            lifecycles.push(lifecycle(
+                open_creation.take().unwrap_or_default(),
+                Some(deletion),
+            ));
+        }
+        lifecycles.extend(block.lifecycles);
+        if let Some(creation) = block.trailing_creation {
+            if let Some(previous_creation) = open_creation.replace(creation) {
+                // The deletion between the creations is not indexed yet
+                lifecycles.push(lifecycle(previous_creation, None));
+            }
+        }
+    }
+    if let Some(creation) = open_creation {
+        lifecycles.push(lifecycle(creation, None));
+    }
+    lifecycles
+}
+
+/// Upserts the latest balances, storage usage and code hash of the changed accounts.
+/// The rows are overwritten only by the changes from the later blocks
+pub async fn handle_account_state(
+    pool: &actix_diesel::Database<PgConnection>,
+    state_changes: &[near_indexer_primitives::views::StateChangeWithCauseView],
+    block_height: near_indexer_primitives::types::BlockHeight,
+) -> anyhow::Result<()> {
+    if state_changes.is_empty() {
+        return Ok(());
+    }
+
+    // The state changes are ordered, the last one is the state at the end of the block
+    let mut account_states = HashMap::<String, models::accounts::AccountState>::new();
+    for state_change in state_changes {
+        match &state_change.value {
+            near_indexer_primitives::views::StateChangeValueView::AccountUpdate {
+                account_id,
+                account,
+            } => {
+                account_states.insert(
+                    account_id.to_string(),
+                    models::accounts::AccountState::from_account_view(
+                        account_id,
+                        account,
+                        block_height,
+                    ),
+                );
+            }
+            near_indexer_primitives::views::StateChangeValueView::AccountDeletion {
+                account_id,
+            } => {
+                account_states.insert(
+                    account_id.to_string(),
+                    models::accounts::AccountState::new_deleted(account_id, block_height),
+                );
+            }
+            _ => continue,
+        }
+    }
+
+    if account_states.is_empty() {
+        return Ok(());
+    }
+
+    let account_state_models: Vec<models::accounts::AccountState> =
+        account_states.into_values().collect();
+    crate::await_retry_or_panic!(
+        diesel::insert_into(schema::account_state::table)
+            .values(account_state_models.clone())
+            .on_conflict_do_nothing()
+            .execute_async(pool),
+        10,
+        "AccountState was stored to database".to_string(),
+        &account_state_models
+    );
+
+    for value in account_state_models {
+        let target = schema::account_state::table
+            .filter(schema::account_state::dsl::account_id.eq(value.account_id.clone()))
+            .filter(
+                schema::account_state::dsl::last_update_block_height
+                    .lt(value.last_update_block_height.clone()),
+            );
+
+        crate::await_retry_or_panic!(
+            diesel::update(target.clone())
+                .set((
+                    schema::account_state::dsl::nonstaked_balance
+                        .eq(value.nonstaked_balance.clone()),
+                    schema::account_state::dsl::staked_balance.eq(value.staked_balance.clone()),
+                    schema::account_state::dsl::storage_usage.eq(value.storage_usage.clone()),
+                    schema::account_state::dsl::code_hash.eq(value.code_hash.clone()),
+                    schema::account_state::dsl::is_deleted.eq(value.is_deleted),
+                    schema::account_state::dsl::last_update_block_height
+                        .eq(value.last_update_block_height.clone()),
+                ))
+                .execute_async(pool),
+            10,
+            "AccountState was updated".to_string(),
+            &value.account_id
+        );
+    }
+
     Ok(())
 }
 
@@ -271,5 +680,141 @@ pub(crate) async fn store_accounts_from_genesis(
         &accounts_models
     );
 
+    let account_lifecycle_models: Vec<models::accounts::AccountLifecycle> = accounts_models
+        .iter()
+        .map(models::accounts::AccountLifecycle::new_from_genesis)
+        .collect();
+    crate::await_retry_or_panic!(
+        diesel::insert_into(schema::account_lifecycles::table)
+            .values(account_lifecycle_models.clone())
+            .on_conflict_do_nothing()
+            .execute_async(&pool),
+        10,
+        "Failed to store AccountLifecycles from genesis".to_string(),
+        &account_lifecycle_models
+    );
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lifecycle(
+        created: Option<(&str, u64)>,
+        deleted: Option<(&str, u64)>,
+    ) -> models::accounts::AccountLifecycle {
+        models::accounts::AccountLifecycle {
+            account_id: "alice.near".to_string(),
+            created_by_receipt_id: created.map(|(receipt_id, _)| receipt_id.to_string()),
+            created_in_block_height: created.map(|(_, block_height)| block_height.into()),
+            deleted_by_receipt_id: deleted.map(|(receipt_id, _)| receipt_id.to_string()),
+            deleted_in_block_height: deleted.map(|(_, block_height)| block_height.into()),
+        }
+    }
+
+    /// Indexes the blocks one by one, each with the lifecycles stored by the previous ones
+    fn index_blocks(
+        blocks: &[(u64, &[(&str, AccountLifecycleChange)])],
+    ) -> Vec<models::accounts::AccountLifecycle> {
+        blocks
+            .iter()
+            .fold(vec![], |stored_lifecycles, (block_height, changes)| {
+                let changes: Vec<(String, AccountLifecycleChange)> = changes
+                    .iter()
+                    .map(|(receipt_id, change)| (receipt_id.to_string(), *change))
+                    .collect();
+                compose_account_lifecycles("alice.near", stored_lifecycles, &changes, *block_height)
+            })
+    }
+
+    const CREATED_10: (u64, &[(&str, AccountLifecycleChange)]) =
+        (10, &[("c10", AccountLifecycleChange::Created)]);
+    const DELETED_20: (u64, &[(&str, AccountLifecycleChange)]) =
+        (20, &[("d20", AccountLifecycleChange::Deleted)]);
+    const CREATED_30: (u64, &[(&str, AccountLifecycleChange)]) =
+        (30, &[("c30", AccountLifecycleChange::Created)]);
+
+    #[test]
+    fn does_not_depend_on_the_order_of_the_blocks() {
+        let expected = vec![
+            lifecycle(Some(("c10", 10)), Some(("d20", 20))),
+            lifecycle(Some(("c30", 30)), None),
+        ];
+        assert_eq!(
+            index_blocks(&[CREATED_10, DELETED_20, CREATED_30]),
+            expected
+        );
+        assert_eq!(
+            index_blocks(&[CREATED_30, DELETED_20, CREATED_10]),
+            expected
+        );
+        assert_eq!(
+            index_blocks(&[DELETED_20, CREATED_30, CREATED_10]),
+            expected
+        );
+        assert_eq!(
+            index_blocks(&[CREATED_10, CREATED_30, DELETED_20]),
+            expected
+        );
+    }
+
+    #[test]
+    fn keeps_the_deletion_without_the_known_creation() {
+        assert_eq!(
+            index_blocks(&[DELETED_20, CREATED_30]),
+            vec![
+                lifecycle(None, Some(("d20", 20))),
+                lifecycle(Some(("c30", 30)), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn reindexing_the_block_does_not_change_the_lifecycles() {
+        let recreated_in_one_block: (u64, &[(&str, AccountLifecycleChange)]) = (
+            20,
+            &[
+                ("d20", AccountLifecycleChange::Deleted),
+                ("c20", AccountLifecycleChange::Created),
+                ("d20-2", AccountLifecycleChange::Deleted),
+                ("c20-2", AccountLifecycleChange::Created),
+            ],
+        );
+        let expected = vec![
+            lifecycle(Some(("c10", 10)), Some(("d20", 20))),
+            lifecycle(Some(("c20", 20)), Some(("d20-2", 20))),
+            lifecycle(Some(("c20-2", 20)), None),
+        ];
+        assert_eq!(
+            index_blocks(&[CREATED_10, recreated_in_one_block]),
+            expected
+        );
+        assert_eq!(
+            index_blocks(&[CREATED_10, recreated_in_one_block, recreated_in_one_block]),
+            expected
+        );
+        assert_eq!(
+            index_blocks(&[recreated_in_one_block, CREATED_10, recreated_in_one_block]),
+            expected
+        );
+    }
+
+    #[test]
+    fn closes_the_genesis_lifecycle() {
+        let genesis_lifecycle = models::accounts::AccountLifecycle {
+            created_in_block_height: Some(9.into()),
+            ..lifecycle(None, None)
+        };
+        let changes = vec![("d20".to_string(), AccountLifecycleChange::Deleted)];
+        assert_eq!(
+            compose_account_lifecycles("alice.near", vec![genesis_lifecycle.clone()], &changes, 20),
+            vec![models::accounts::AccountLifecycle {
+                deleted_by_receipt_id: Some("d20".to_string()),
+                deleted_in_block_height: Some(20.into()),
+                ..genesis_lifecycle
+            }]
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+use actix_diesel::dsl::AsyncRunQueryDsl;
+use anyhow::Context;
+use bigdecimal::BigDecimal;
+use diesel::pg::upsert::excluded;
+use diesel::{Connection, ExpressionMethods, PgConnection, RunQueryDsl};
+
+use near_indexer_primitives::views::{ActionView, ReceiptEnumView};
+
+use crate::adapters::aggregated::DAY_NANOSECONDS;
+use crate::adapters::CHUNK_SIZE_FOR_BATCH_INSERT;
+use crate::models::aggregated::account_interactions::AccountInteraction;
+use crate::models::enums::ActionKind;
+use crate::schema;
+
+/// The name of the aggregation in `aggregated__processed_blocks`
+const AGGREGATION_NAME: &str = "account_interactions";
+
+sql_function! {
+    #[sql_name = "aggregated__rebuild_account_interactions"]
+    fn rebuild_account_interactions_for_days(
+        from_day_timestamp: diesel::sql_types::Numeric,
+        to_day_timestamp: diesel::sql_types::Numeric
+    ) -> diesel::sql_types::Integer;
+}
+
+/// Adds the actions of the block to the daily interactions.
+/// Each block is counted once, even if it is indexed again:
+/// the block is marked as processed in the same transaction with the updates
+pub async fn update_account_interactions(
+    pool: &actix_diesel::Database<PgConnection>,
+    shards: &[near_indexer_primitives::IndexerShard],
+    block_height: near_indexer_primitives::types::BlockHeight,
+    block_timestamp: u64,
+) -> anyhow::Result<()> {
+    let account_interaction_models = compose_account_interactions(shards, block_timestamp);
+
+    crate::await_retry_or_panic!(
+        pool.get({
+            let account_interaction_models = account_interaction_models.clone();
+            move |conn| {
+                conn.transaction::<_, diesel::result::Error, _>(|| {
+                    if super::mark_block_processed(conn, AGGREGATION_NAME, block_height)? {
+                        add_account_interactions(conn, &account_interaction_models)?;
+                    }
+                    Ok(())
+                })
+            }
+        }),
+        10,
+        "AccountInteractions of the block were stored to database".to_string(),
+        &account_interaction_models
+    );
+
+    Ok(())
+}
+
+fn add_account_interactions(
+    conn: &PgConnection,
+    account_interaction_models: &[AccountInteraction],
+) -> diesel::QueryResult<()> {
+    use schema::aggregated__account_interactions::dsl;
+
+    for chunk in account_interaction_models.chunks(CHUNK_SIZE_FOR_BATCH_INSERT) {
+        diesel::insert_into(schema::aggregated__account_interactions::table)
+            .values(chunk.to_vec())
+            .on_conflict((
+                dsl::predecessor_account_id,
+                dsl::receiver_account_id,
+                dsl::day_timestamp,
+                dsl::action_kind,
+            ))
+            .do_update()
+            .set((
+                dsl::actions_count.eq(dsl::actions_count + excluded(dsl::actions_count)),
+                dsl::total_deposit.eq(dsl::total_deposit + excluded(dsl::total_deposit)),
+                dsl::total_gas_burnt.eq(dsl::total_gas_burnt + excluded(dsl::total_gas_burnt)),
+            ))
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// Recomputes the interactions of the given UTC days (inclusive) from the indexed tables,
+/// the timestamps are rounded down to the start of the day.
+/// Returns the number of the stored rows
+pub async fn rebuild_account_interactions(
+    pool: &actix_diesel::Database<PgConnection>,
+    from_day_timestamp: u64,
+    to_day_timestamp: u64,
+) -> anyhow::Result<i32> {
+    anyhow::ensure!(
+        from_day_timestamp <= to_day_timestamp,
+        "The start of the range {} is greater than the end {}",
+        from_day_timestamp,
+        to_day_timestamp,
+    );
+
+    diesel::select(rebuild_account_interactions_for_days(
+        BigDecimal::from(from_day_timestamp),
+        BigDecimal::from(to_day_timestamp),
+    ))
+    .first_async::<i32>(pool)
+    .await
+    .context("DB Error")
+}
+
+fn compose_account_interactions(
+    shards: &[near_indexer_primitives::IndexerShard],
+    block_timestamp: u64,
+) -> Vec<AccountInteraction> {
+    // (actions count, total deposit, total gas burnt)
+    let mut interactions = HashMap::<(String, String, ActionKind), (i64, u128, u128)>::new();
+
+    for outcome in shards
+        .iter()
+        .flat_map(|shard| shard.receipt_execution_outcomes.iter())
+    {
+        let actions = match &outcome.receipt.receipt {
+            ReceiptEnumView::Action { actions, .. } => actions,
+            ReceiptEnumView::Data { .. } => continue,
+        };
+
+        let mut receipt_interactions = HashMap::<ActionKind, (i64, u128)>::new();
+        for action in actions {
+            let (action_kind, deposit) = action_kind_and_deposit(action);
+            let receipt_interaction = receipt_interactions.entry(action_kind).or_default();
+            receipt_interaction.0 += 1;
+            receipt_interaction.1 += deposit;
+        }
+
+        // The gas is burnt by the whole receipt, so it goes to each action kind of the receipt once
+        for (action_kind, (actions_count, deposit)) in receipt_interactions {
+            let interaction = interactions
+                .entry((
+                    outcome.receipt.predecessor_id.to_string(),
+                    outcome.receipt.receiver_id.to_string(),
+                    action_kind,
+                ))
+                .or_default();
+            interaction.0 += actions_count;
+            interaction.1 += deposit;
+            interaction.2 += u128::from(outcome.execution_outcome.outcome.gas_burnt);
+        }
+    }
+
+    let day_timestamp = BigDecimal::from(block_timestamp - block_timestamp % DAY_NANOSECONDS);
+    interactions
+        .into_iter()
+        .map(
+            |(
+                (predecessor_account_id, receiver_account_id, action_kind),
+                (actions_count, total_deposit, total_gas_burnt),
+            )| AccountInteraction {
+                predecessor_account_id,
+                receiver_account_id,
+                day_timestamp: day_timestamp.clone(),
+                action_kind,
+                actions_count,
+                total_deposit: crate::models::u128_to_big_decimal(total_deposit),
+                total_gas_burnt: crate::models::u128_to_big_decimal(total_gas_burnt),
+            },
+        )
+        .collect()
+}
+
+fn action_kind_and_deposit(action: &ActionView) -> (ActionKind, u128) {
+    match action {
+        ActionView::CreateAccount => (ActionKind::CreateAccount, 0),
+        ActionView::DeployContract { .. } => (ActionKind::DeployContract, 0),
+        ActionView::FunctionCall { deposit, .. } => (ActionKind::FunctionCall, *deposit),
+        ActionView::Transfer { deposit } => (ActionKind::Transfer, *deposit),
+        ActionView::Stake { .. } => (ActionKind::Stake, 0),
+        ActionView::AddKey { .. } => (ActionKind::AddKey, 0),
+        ActionView::DeleteKey { .. } => (ActionKind::DeleteKey, 0),
+        ActionView::DeleteAccount { .. } => (ActionKind::DeleteAccount, 0),
+        ActionView::Delegate { .. } => (ActionKind::DelegateAction, 0),
+    }
+}
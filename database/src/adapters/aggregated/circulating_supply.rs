@@ -1,5 +1,7 @@
 use actix_diesel::dsl::AsyncRunQueryDsl;
+use anyhow::Context;
 use bigdecimal::BigDecimal;
+use diesel::pg::upsert::excluded;
 use diesel::{ExpressionMethods, PgConnection, QueryDsl};
 use tracing::error;
 
@@ -37,6 +39,49 @@ pub async fn add_circulating_supply(
     }
 }
 
+/// Stores the circulating supply, the previously computed values for the same block are overwritten
+pub async fn replace_circulating_supply(
+    pool: &actix_diesel::Database<PgConnection>,
+    stats: &CirculatingSupply,
+) -> anyhow::Result<()> {
+    use schema::aggregated__circulating_supply::dsl;
+
+    crate::await_retry_or_panic!(
+        diesel::insert_into(schema::aggregated__circulating_supply::table)
+            .values(stats.to_owned())
+            .on_conflict(dsl::computed_at_block_hash)
+            .do_update()
+            .set((
+                dsl::circulating_tokens_supply.eq(excluded(dsl::circulating_tokens_supply)),
+                dsl::total_tokens_supply.eq(excluded(dsl::total_tokens_supply)),
+                dsl::total_lockup_contracts_count.eq(excluded(dsl::total_lockup_contracts_count)),
+                dsl::unfinished_lockup_contracts_count
+                    .eq(excluded(dsl::unfinished_lockup_contracts_count)),
+                dsl::foundation_locked_tokens.eq(excluded(dsl::foundation_locked_tokens)),
+                dsl::lockups_locked_tokens.eq(excluded(dsl::lockups_locked_tokens)),
+            ))
+            .execute_async(pool),
+        10,
+        "Circulating Supply was replaced in database".to_string(),
+        &stats
+    );
+    Ok(())
+}
+
+pub async fn get_circulating_supply_for_timestamp(
+    pool: &actix_diesel::Database<PgConnection>,
+    timestamp: u64,
+) -> anyhow::Result<Option<CirculatingSupply>> {
+    schema::aggregated__circulating_supply::table
+        .filter(
+            schema::aggregated__circulating_supply::dsl::computed_at_block_timestamp
+                .eq(BigDecimal::from(timestamp)),
+        )
+        .get_optional_result_async::<CirculatingSupply>(pool)
+        .await
+        .context("DB Error")
+}
+
 pub async fn get_precomputed_circulating_supply_for_timestamp(
     pool: &actix_diesel::Database<PgConnection>,
     timestamp: u64,
@@ -0,0 +1,234 @@
+use std::collections::{HashMap, HashSet};
+
+use actix_diesel::dsl::AsyncRunQueryDsl;
+use anyhow::Context;
+use bigdecimal::BigDecimal;
+use diesel::pg::upsert::excluded;
+use diesel::{Connection, ExpressionMethods, PgConnection, RunQueryDsl};
+
+use near_indexer_primitives::views::{ActionView, ReceiptEnumView};
+
+use crate::adapters::aggregated::DAY_NANOSECONDS;
+use crate::adapters::CHUNK_SIZE_FOR_BATCH_INSERT;
+use crate::models::aggregated::contract_usage::{ContractUsage, ContractUsageCaller};
+use crate::schema;
+
+/// The name of the aggregation in `aggregated__processed_blocks`
+const AGGREGATION_NAME: &str = "contract_usage";
+
+sql_function! {
+    #[sql_name = "aggregated__rebuild_contract_usage"]
+    fn rebuild_contract_usage_for_days(
+        from_day_timestamp: diesel::sql_types::Numeric,
+        to_day_timestamp: diesel::sql_types::Numeric
+    ) -> diesel::sql_types::Integer;
+}
+
+/// The calls of one method of the contract within the block
+#[derive(Default)]
+struct MethodCalls {
+    calls_count: i64,
+    total_gas_burnt: u128,
+    total_deposit: u128,
+    callers: HashSet<String>,
+}
+
+/// Adds the function calls of the block to the daily contract usage.
+/// Each block is counted once, even if it is indexed again:
+/// the block is marked as processed in the same transaction with the updates
+pub async fn update_contract_usage(
+    pool: &actix_diesel::Database<PgConnection>,
+    shards: &[near_indexer_primitives::IndexerShard],
+    block_height: near_indexer_primitives::types::BlockHeight,
+    block_timestamp: u64,
+) -> anyhow::Result<()> {
+    let method_calls = collect_method_calls(shards);
+    if method_calls.is_empty() {
+        return Ok(());
+    }
+
+    let day_timestamp = BigDecimal::from(block_timestamp - block_timestamp % DAY_NANOSECONDS);
+    let caller_models: Vec<ContractUsageCaller> = method_calls
+        .iter()
+        .flat_map(|((contract_account_id, method_name), calls)| {
+            calls
+                .callers
+                .iter()
+                .map(|caller_account_id| ContractUsageCaller {
+                    contract_account_id: contract_account_id.clone(),
+                    day_timestamp: day_timestamp.clone(),
+                    method_name: method_name.clone(),
+                    caller_account_id: caller_account_id.clone(),
+                })
+        })
+        .collect();
+    // `unique_callers_count` is known only after the callers are stored
+    let contract_usage_models: Vec<ContractUsage> = method_calls
+        .into_iter()
+        .map(
+            |((contract_account_id, method_name), calls)| ContractUsage {
+                contract_account_id,
+                day_timestamp: day_timestamp.clone(),
+                method_name,
+                calls_count: calls.calls_count,
+                unique_callers_count: 0,
+                total_gas_burnt: crate::models::u128_to_big_decimal(calls.total_gas_burnt),
+                total_deposit: crate::models::u128_to_big_decimal(calls.total_deposit),
+            },
+        )
+        .collect();
+
+    // The callers are counted as seen only if the usage is updated as well
+    crate::await_retry_or_panic!(
+        pool.get({
+            let caller_models = caller_models.clone();
+            let contract_usage_models = contract_usage_models.clone();
+            move |conn| {
+                conn.transaction::<_, diesel::result::Error, _>(|| {
+                    if super::mark_block_processed(conn, AGGREGATION_NAME, block_height)? {
+                        let new_callers_counts = add_contract_usage_callers(conn, &caller_models)?;
+                        add_contract_usage(conn, contract_usage_models, &new_callers_counts)?;
+                    }
+                    Ok(())
+                })
+            }
+        }),
+        10,
+        "ContractUsage of the block was stored to database".to_string(),
+        &contract_usage_models
+    );
+
+    Ok(())
+}
+
+/// Only the callers which are not seen during the day yet are counted
+fn add_contract_usage_callers(
+    conn: &PgConnection,
+    caller_models: &[ContractUsageCaller],
+) -> diesel::QueryResult<HashMap<(String, String), i64>> {
+    let mut new_callers_counts = HashMap::<(String, String), i64>::new();
+    for chunk in caller_models.chunks(CHUNK_SIZE_FOR_BATCH_INSERT) {
+        let new_callers: Vec<ContractUsageCaller> =
+            diesel::insert_into(schema::aggregated__contract_usage_callers::table)
+                .values(chunk.to_vec())
+                .on_conflict_do_nothing()
+                .get_results(conn)?;
+        for new_caller in new_callers {
+            *new_callers_counts
+                .entry((new_caller.contract_account_id, new_caller.method_name))
+                .or_default() += 1;
+        }
+    }
+    Ok(new_callers_counts)
+}
+
+fn add_contract_usage(
+    conn: &PgConnection,
+    mut contract_usage_models: Vec<ContractUsage>,
+    new_callers_counts: &HashMap<(String, String), i64>,
+) -> diesel::QueryResult<()> {
+    use schema::aggregated__contract_usage::dsl;
+
+    for contract_usage in contract_usage_models.iter_mut() {
+        contract_usage.unique_callers_count = new_callers_counts
+            .get(&(
+                contract_usage.contract_account_id.clone(),
+                contract_usage.method_name.clone(),
+            ))
+            .copied()
+            .unwrap_or_default();
+    }
+
+    for chunk in contract_usage_models.chunks(CHUNK_SIZE_FOR_BATCH_INSERT) {
+        diesel::insert_into(schema::aggregated__contract_usage::table)
+            .values(chunk.to_vec())
+            .on_conflict((
+                dsl::contract_account_id,
+                dsl::day_timestamp,
+                dsl::method_name,
+            ))
+            .do_update()
+            .set((
+                dsl::calls_count.eq(dsl::calls_count + excluded(dsl::calls_count)),
+                dsl::unique_callers_count
+                    .eq(dsl::unique_callers_count + excluded(dsl::unique_callers_count)),
+                dsl::total_gas_burnt.eq(dsl::total_gas_burnt + excluded(dsl::total_gas_burnt)),
+                dsl::total_deposit.eq(dsl::total_deposit + excluded(dsl::total_deposit)),
+            ))
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// Recomputes the contract usage of the given UTC days (inclusive) from the indexed tables,
+/// the timestamps are rounded down to the start of the day.
+/// Returns the number of the stored rows
+pub async fn rebuild_contract_usage(
+    pool: &actix_diesel::Database<PgConnection>,
+    from_day_timestamp: u64,
+    to_day_timestamp: u64,
+) -> anyhow::Result<i32> {
+    anyhow::ensure!(
+        from_day_timestamp <= to_day_timestamp,
+        "The start of the range {} is greater than the end {}",
+        from_day_timestamp,
+        to_day_timestamp,
+    );
+
+    diesel::select(rebuild_contract_usage_for_days(
+        BigDecimal::from(from_day_timestamp),
+        BigDecimal::from(to_day_timestamp),
+    ))
+    .first_async::<i32>(pool)
+    .await
+    .context("DB Error")
+}
+
+fn collect_method_calls(
+    shards: &[near_indexer_primitives::IndexerShard],
+) -> HashMap<(String, String), MethodCalls> {
+    let mut method_calls = HashMap::<(String, String), MethodCalls>::new();
+
+    for outcome in shards
+        .iter()
+        .flat_map(|shard| shard.receipt_execution_outcomes.iter())
+    {
+        let actions = match &outcome.receipt.receipt {
+            ReceiptEnumView::Action { actions, .. } => actions,
+            ReceiptEnumView::Data { .. } => continue,
+        };
+
+        // (calls count, deposit) of each method called by the receipt
+        let mut receipt_calls = HashMap::<String, (i64, u128)>::new();
+        for action in actions {
+            if let ActionView::FunctionCall {
+                method_name,
+                deposit,
+                ..
+            } = action
+            {
+                // The same escaping as in `action_receipt_actions.args`
+                let receipt_call = receipt_calls
+                    .entry(method_name.escape_default().to_string())
+                    .or_default();
+                receipt_call.0 += 1;
+                receipt_call.1 += deposit;
+            }
+        }
+
+        // The gas is burnt by the whole receipt, so it goes to each method of the receipt once
+        for (method_name, (calls_count, deposit)) in receipt_calls {
+            let calls = method_calls
+                .entry((outcome.receipt.receiver_id.to_string(), method_name))
+                .or_default();
+            calls.calls_count += calls_count;
+            calls.total_deposit += deposit;
+            calls.total_gas_burnt += u128::from(outcome.execution_outcome.outcome.gas_burnt);
+            calls
+                .callers
+                .insert(outcome.receipt.predecessor_id.to_string());
+        }
+    }
+
+    method_calls
+}
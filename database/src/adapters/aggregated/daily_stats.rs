@@ -0,0 +1,44 @@
+use actix_diesel::dsl::AsyncRunQueryDsl;
+use anyhow::Context;
+use bigdecimal::BigDecimal;
+use diesel::{ExpressionMethods, PgConnection, QueryDsl};
+
+use crate::models::aggregated::daily_stats::DailyStats;
+use crate::schema;
+
+sql_function! {
+    #[sql_name = "aggregated__compute_daily_stats"]
+    fn compute_daily_stats_for_day(day_timestamp: diesel::sql_types::Numeric) -> diesel::sql_types::Integer;
+}
+
+/// Computes the stats of the UTC day from the indexed tables and stores them,
+/// replacing the previous values. `day_timestamp` is the start of the day in nanoseconds.
+/// Returns `None` if there are no indexed blocks within the day
+pub async fn compute_daily_stats(
+    pool: &actix_diesel::Database<PgConnection>,
+    day_timestamp: u64,
+) -> anyhow::Result<Option<DailyStats>> {
+    let stored_rows_count =
+        diesel::select(compute_daily_stats_for_day(BigDecimal::from(day_timestamp)))
+            .first_async::<i32>(pool)
+            .await
+            .context("DB Error")?;
+    if stored_rows_count == 0 {
+        return Ok(None);
+    }
+    get_daily_stats(pool, day_timestamp).await
+}
+
+/// `day_timestamp` is the start of the UTC day in nanoseconds
+pub async fn get_daily_stats(
+    pool: &actix_diesel::Database<PgConnection>,
+    day_timestamp: u64,
+) -> anyhow::Result<Option<DailyStats>> {
+    schema::aggregated__daily_stats::table
+        .filter(
+            schema::aggregated__daily_stats::dsl::day_timestamp.eq(BigDecimal::from(day_timestamp)),
+        )
+        .get_optional_result_async::<DailyStats>(pool)
+        .await
+        .context("DB Error")
+}
@@ -0,0 +1,83 @@
+use actix_diesel::dsl::AsyncRunQueryDsl;
+use anyhow::Context;
+use bigdecimal::BigDecimal;
+use diesel::PgConnection;
+
+sql_function! {
+    #[sql_name = "aggregated__compute_epoch_supply"]
+    fn compute_epoch_supply_until(
+        until_block_timestamp: diesel::sql_types::Numeric
+    ) -> diesel::sql_types::Integer;
+}
+
+/// Stores the total supply changes (minted and burnt tokens) of all the epochs
+/// finished before `until_block_timestamp`, the already computed epochs are kept as is.
+/// Returns the number of the newly computed epochs
+pub async fn compute_epoch_supply(
+    pool: &actix_diesel::Database<PgConnection>,
+    until_block_timestamp: u64,
+) -> anyhow::Result<i32> {
+    diesel::select(compute_epoch_supply_until(BigDecimal::from(
+        until_block_timestamp,
+    )))
+    .first_async::<i32>(pool)
+    .await
+    .context("DB Error")
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel::connection::SimpleConnection;
+    use diesel::{Connection, QueryDsl, RunQueryDsl};
+
+    use super::*;
+    use crate::models::aggregated::epoch_supply::EpochSupply;
+    use crate::schema::aggregated__epoch_supply;
+
+    /// Epoch `E1` spans blocks 10-11 and mints 50 tokens at its first block.
+    /// Epoch `E2` has a chunk indexed before `balance_burnt` was stored
+    const FIXTURES: &str = "
+        INSERT INTO blocks (block_height, block_hash, prev_block_hash, block_timestamp, total_supply, gas_price, author_account_id)
+        VALUES (9, 'b9', 'b8', 900, 1000, 1, 'test.near'),
+               (10, 'b10', 'b9', 1000, 1047, 1, 'test.near'),
+               (11, 'b11', 'b10', 1100, 1043, 1, 'test.near'),
+               (12, 'b12', 'b11', 1200, 1093, 1, 'test.near');
+        INSERT INTO chunks (included_in_block_hash, chunk_hash, shard_id, signature, gas_limit, gas_used, author_account_id, balance_burnt)
+        VALUES ('b10', 'c10-0', 0, '', 0, 0, 'test.near', 3),
+               ('b11', 'c11-0', 0, '', 0, 0, 'test.near', 4),
+               ('b11', 'c11-1', 1, '', 0, 0, 'test.near', 0),
+               ('b12', 'c12-0', 0, '', 0, 0, 'test.near', NULL);
+        INSERT INTO epochs (epoch_id, epoch_height, start_block_height, end_block_height)
+        VALUES ('E1', 1, 10, 11),
+               ('E2', 2, 12, 12);
+    ";
+
+    /// minted_tokens = total_supply_after - total_supply_before + burnt_tokens
+    #[test]
+    #[ignore = "requires DATABASE_URL with the migrations applied"]
+    fn computes_minted_tokens_from_supply_and_burnt_tokens() {
+        let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL is required");
+        let conn = PgConnection::establish(&database_url).unwrap();
+        conn.test_transaction::<_, diesel::result::Error, _>(|| {
+            conn.batch_execute(FIXTURES)?;
+
+            let stored_rows_count: i32 =
+                diesel::select(compute_epoch_supply_until(BigDecimal::from(2000)))
+                    .get_result(&conn)?;
+            assert_eq!(stored_rows_count, 1);
+
+            let epoch_supply: EpochSupply = aggregated__epoch_supply::table.first(&conn)?;
+            assert_eq!(epoch_supply.epoch_id, "E1");
+            assert_eq!(epoch_supply.total_supply_before, BigDecimal::from(1000));
+            assert_eq!(epoch_supply.total_supply_after, BigDecimal::from(1043));
+            assert_eq!(epoch_supply.burnt_tokens, BigDecimal::from(7));
+            assert_eq!(epoch_supply.minted_tokens, BigDecimal::from(50));
+
+            let stored_rows_count: i32 =
+                diesel::select(compute_epoch_supply_until(BigDecimal::from(2000)))
+                    .get_result(&conn)?;
+            assert_eq!(stored_rows_count, 0);
+            Ok(())
+        });
+    }
+}
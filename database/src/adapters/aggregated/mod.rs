@@ -1 +1,29 @@
+pub mod account_interactions;
 pub mod circulating_supply;
+pub mod contract_usage;
+pub mod daily_stats;
+pub mod epoch_supply;
+
+/// The incremental aggregations group the blocks by UTC day
+const DAY_NANOSECONDS: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+/// Marks the block as processed by the aggregation, should be called in the transaction
+/// with the aggregation updates, so the block is either counted in full or not counted at all.
+/// Returns `false` if the block was already processed
+fn mark_block_processed(
+    conn: &diesel::PgConnection,
+    aggregation: &str,
+    block_height: near_indexer_primitives::types::BlockHeight,
+) -> diesel::QueryResult<bool> {
+    use diesel::{ExpressionMethods, RunQueryDsl};
+
+    diesel::insert_into(crate::schema::aggregated__processed_blocks::table)
+        .values((
+            crate::schema::aggregated__processed_blocks::dsl::aggregation.eq(aggregation),
+            crate::schema::aggregated__processed_blocks::dsl::block_height
+                .eq(bigdecimal::BigDecimal::from(block_height)),
+        ))
+        .on_conflict_do_nothing()
+        .execute(conn)
+        .map(|inserted_rows_count| inserted_rows_count > 0)
+}
@@ -0,0 +1,492 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use actix_diesel::dsl::AsyncRunQueryDsl;
+use actix_diesel::Database;
+use anyhow::Context;
+use bigdecimal::{BigDecimal, ToPrimitive};
+use diesel::pg::expression::array_comparison::any;
+use diesel::{Connection, ExpressionMethods, JoinOnDsl, PgConnection, QueryDsl, RunQueryDsl};
+use tracing::{info, warn};
+
+use near_indexer_primitives::views;
+
+use crate::adapters::{assets, CHUNK_SIZE_FOR_BATCH_INSERT};
+use crate::models;
+#[cfg(feature = "fungible_token_events")]
+use crate::models::enums::ActionKind;
+use crate::models::enums::ExecutionOutcomeStatus;
+use crate::schema;
+
+/// We don't want to load the whole range into memory at once
+const BLOCKS_PER_BATCH: u64 = 100;
+
+/// Execution outcome with its receipt as it is stored in the database
+#[derive(Queryable, Debug)]
+struct StoredOutcome {
+    block_height: BigDecimal,
+    block_timestamp: BigDecimal,
+    shard_id: BigDecimal,
+    receipt_id: String,
+    executed_in_block_hash: String,
+    executor_account_id: String,
+    gas_burnt: BigDecimal,
+    tokens_burnt: BigDecimal,
+    status: ExecutionOutcomeStatus,
+    logs: Option<Vec<String>>,
+    return_value_base64: Option<String>,
+    failure: Option<serde_json::Value>,
+    predecessor_account_id: String,
+    receiver_account_id: String,
+    signer_account_id: String,
+    signer_public_key: String,
+    gas_price: BigDecimal,
+}
+
+/// The lines of the assets tables composed for a batch of blocks
+#[derive(Debug, Clone, Default)]
+struct BatchEvents {
+    receipt_ids: Vec<String>,
+    #[cfg(feature = "fungible_token_events")]
+    ft_events: Vec<models::assets::fungible_token_events::FungibleTokenEvent>,
+    nft_events: Vec<models::assets::non_fungible_token_events::NonFungibleTokenEvent>,
+    mt_events: Vec<models::assets::multi_token_events::MultiTokenEvent>,
+    invalid_events: Vec<models::assets::invalid_events::InvalidEvent>,
+}
+
+/// Extracts the events from the logs stored in `execution_outcomes` table
+/// and rewrites the assets tables for the given range of blocks (inclusive), without touching NEAR Lake.
+/// The numbering of the events in the shard depends on the filter and the options,
+/// so the previously stored events of the receipts are removed before we write them again.
+/// The shards with the outcomes indexed without logs can't be numbered the same way, they are kept as is
+pub async fn backfill_events(
+    pool: &Database<PgConnection>,
+    from_block_height: u64,
+    to_block_height: u64,
+    contracts_filter_arc: &assets::filter::ContractsFilterArc,
+    options: assets::events::EventsOptions,
+) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        from_block_height <= to_block_height,
+        "`--from` block height {} is greater than `--to` block height {}",
+        from_block_height,
+        to_block_height,
+    );
+
+    for batch_start in (from_block_height..=to_block_height).step_by(BLOCKS_PER_BATCH as usize) {
+        let batch_end = std::cmp::min(batch_start + BLOCKS_PER_BATCH - 1, to_block_height);
+        let stored_outcomes = load_stored_outcomes(pool, batch_start, batch_end).await?;
+        #[cfg(feature = "fungible_token_events")]
+        let mut function_calls = if options.detect_legacy_ft_transfers {
+            load_function_calls(pool, batch_start, batch_end).await?
+        } else {
+            HashMap::new()
+        };
+        #[cfg(not(feature = "fungible_token_events"))]
+        let mut function_calls = HashMap::new();
+
+        let (shards, incomplete_shards_count) =
+            group_by_block_and_shard(stored_outcomes, &mut function_calls)?;
+        if incomplete_shards_count > 0 {
+            warn!(
+                target: crate::EXPLORER_DATABASE,
+                "assets::backfill: {} shards in blocks {}..={} have execution outcomes indexed without logs, their events are kept as is",
+                incomplete_shards_count,
+                batch_start,
+                batch_end,
+            );
+        }
+
+        // Taking a snapshot of the rules, the same way we do it during the regular indexing
+        let contracts_filter = contracts_filter_arc.read().await.clone();
+        let mut batch_events = BatchEvents::default();
+        for ((_, block_timestamp), shard) in shards {
+            batch_events.receipt_ids.extend(
+                shard
+                    .receipt_execution_outcomes
+                    .iter()
+                    .map(|outcome| outcome.receipt.receipt_id.to_string()),
+            );
+            let shard_events =
+                assets::events::collect_events(&shard, block_timestamp, &contracts_filter, options);
+            #[cfg(feature = "fungible_token_events")]
+            {
+                batch_events.ft_events.extend(shard_events.ft_events.valid);
+                batch_events
+                    .invalid_events
+                    .extend(shard_events.ft_events.invalid);
+            }
+            batch_events
+                .nft_events
+                .extend(shard_events.nft_events.valid);
+            batch_events
+                .invalid_events
+                .extend(shard_events.nft_events.invalid);
+            batch_events.mt_events.extend(shard_events.mt_events.valid);
+            batch_events
+                .invalid_events
+                .extend(shard_events.mt_events.invalid);
+        }
+
+        crate::await_retry_or_panic!(
+            pool.get({
+                let batch_events = batch_events.clone();
+                move |conn| {
+                    conn.transaction::<_, diesel::result::Error, _>(|| {
+                        delete_stored_events(conn, &batch_events.receipt_ids)?;
+                        insert_events(conn, &batch_events)
+                    })
+                }
+            }),
+            10,
+            "Events of the backfilled blocks were rewritten in database".to_string(),
+            &(batch_start, batch_end)
+        );
+
+        info!(
+            target: crate::EXPLORER_DATABASE,
+            "assets::backfill: events for blocks {}..={} are stored", batch_start, batch_end,
+        );
+    }
+
+    Ok(())
+}
+
+/// All the lines of the receipts are removed, including the invalid entries
+/// and the legacy FT transfers, they are composed again with the options of the current run
+fn delete_stored_events(conn: &PgConnection, receipt_ids: &[String]) -> diesel::QueryResult<()> {
+    #[cfg(feature = "fungible_token_events")]
+    diesel::delete(
+        schema::assets__fungible_token_events::table.filter(
+            schema::assets__fungible_token_events::dsl::emitted_for_receipt_id
+                .eq(any(receipt_ids.to_vec())),
+        ),
+    )
+    .execute(conn)?;
+    diesel::delete(
+        schema::assets__non_fungible_token_events::table.filter(
+            schema::assets__non_fungible_token_events::dsl::emitted_for_receipt_id
+                .eq(any(receipt_ids.to_vec())),
+        ),
+    )
+    .execute(conn)?;
+    diesel::delete(
+        schema::assets__multi_token_events::table.filter(
+            schema::assets__multi_token_events::dsl::emitted_for_receipt_id
+                .eq(any(receipt_ids.to_vec())),
+        ),
+    )
+    .execute(conn)?;
+    diesel::delete(schema::assets__invalid_events::table.filter(
+        schema::assets__invalid_events::dsl::emitted_for_receipt_id.eq(any(receipt_ids.to_vec())),
+    ))
+    .execute(conn)?;
+    Ok(())
+}
+
+fn insert_events(conn: &PgConnection, batch_events: &BatchEvents) -> diesel::QueryResult<()> {
+    #[cfg(feature = "fungible_token_events")]
+    for chunk in batch_events.ft_events.chunks(CHUNK_SIZE_FOR_BATCH_INSERT) {
+        diesel::insert_into(schema::assets__fungible_token_events::table)
+            .values(chunk.to_vec())
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+    }
+    for chunk in batch_events.nft_events.chunks(CHUNK_SIZE_FOR_BATCH_INSERT) {
+        diesel::insert_into(schema::assets__non_fungible_token_events::table)
+            .values(chunk.to_vec())
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+    }
+    for chunk in batch_events.mt_events.chunks(CHUNK_SIZE_FOR_BATCH_INSERT) {
+        diesel::insert_into(schema::assets__multi_token_events::table)
+            .values(chunk.to_vec())
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+    }
+    for chunk in batch_events
+        .invalid_events
+        .chunks(CHUNK_SIZE_FOR_BATCH_INSERT)
+    {
+        diesel::insert_into(schema::assets__invalid_events::table)
+            .values(chunk.to_vec())
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+async fn load_stored_outcomes(
+    pool: &Database<PgConnection>,
+    from_block_height: u64,
+    to_block_height: u64,
+) -> anyhow::Result<Vec<StoredOutcome>> {
+    schema::execution_outcomes::table
+        .inner_join(schema::blocks::table)
+        .inner_join(schema::receipts::table)
+        .inner_join(
+            schema::action_receipts::table.on(schema::action_receipts::dsl::receipt_id
+                .eq(schema::execution_outcomes::dsl::receipt_id)),
+        )
+        .filter(schema::blocks::dsl::block_height.between(
+            BigDecimal::from(from_block_height),
+            BigDecimal::from(to_block_height),
+        ))
+        .select((
+            schema::blocks::dsl::block_height,
+            schema::blocks::dsl::block_timestamp,
+            schema::execution_outcomes::dsl::shard_id,
+            schema::execution_outcomes::dsl::receipt_id,
+            schema::execution_outcomes::dsl::executed_in_block_hash,
+            schema::execution_outcomes::dsl::executor_account_id,
+            schema::execution_outcomes::dsl::gas_burnt,
+            schema::execution_outcomes::dsl::tokens_burnt,
+            schema::execution_outcomes::dsl::status,
+            schema::execution_outcomes::dsl::logs,
+            schema::execution_outcomes::dsl::return_value_base64,
+            schema::execution_outcomes::dsl::failure,
+            schema::receipts::dsl::predecessor_account_id,
+            schema::receipts::dsl::receiver_account_id,
+            schema::action_receipts::dsl::signer_account_id,
+            schema::action_receipts::dsl::signer_public_key,
+            schema::action_receipts::dsl::gas_price,
+        ))
+        .order((
+            schema::blocks::dsl::block_height.asc(),
+            schema::execution_outcomes::dsl::shard_id.asc(),
+            schema::execution_outcomes::dsl::index_in_chunk.asc(),
+        ))
+        .load_async::<StoredOutcome>(pool)
+        .await
+        .context("DB Error")
+}
+
+/// The top-level function calls of the receipts executed in the blocks, in the order of the receipt actions.
+/// The legacy FT transfers are detected from them
+#[cfg(feature = "fungible_token_events")]
+async fn load_function_calls(
+    pool: &Database<PgConnection>,
+    from_block_height: u64,
+    to_block_height: u64,
+) -> anyhow::Result<HashMap<String, Vec<views::ActionView>>> {
+    let stored_actions = schema::action_receipt_actions::table
+        .inner_join(
+            schema::execution_outcomes::table.on(schema::execution_outcomes::dsl::receipt_id
+                .eq(schema::action_receipt_actions::dsl::receipt_id)),
+        )
+        .inner_join(
+            schema::blocks::table.on(schema::blocks::dsl::block_hash
+                .eq(schema::execution_outcomes::dsl::executed_in_block_hash)),
+        )
+        .filter(schema::blocks::dsl::block_height.between(
+            BigDecimal::from(from_block_height),
+            BigDecimal::from(to_block_height),
+        ))
+        .filter(schema::action_receipt_actions::dsl::action_kind.eq(ActionKind::FunctionCall))
+        .filter(schema::action_receipt_actions::dsl::is_delegate_action.eq(false))
+        .select((
+            schema::action_receipt_actions::dsl::receipt_id,
+            schema::action_receipt_actions::dsl::args,
+        ))
+        .order((
+            schema::action_receipt_actions::dsl::receipt_id.asc(),
+            schema::action_receipt_actions::dsl::index_in_action_receipt.asc(),
+        ))
+        .load_async::<(String, serde_json::Value)>(pool)
+        .await
+        .context("DB Error")?;
+
+    let mut function_calls: HashMap<String, Vec<views::ActionView>> = HashMap::new();
+    for (receipt_id, args) in stored_actions {
+        let function_call = restore_function_call(&args).with_context(|| {
+            format!("Failed to restore function call of receipt {}", receipt_id)
+        })?;
+        function_calls
+            .entry(receipt_id)
+            .or_default()
+            .push(function_call);
+    }
+    Ok(function_calls)
+}
+
+/// `action_receipt_actions.args` keeps the arguments both as base64 and JSON,
+/// the method name is the only escaped field, NEP-141 methods are not affected by that
+#[cfg(feature = "fungible_token_events")]
+fn restore_function_call(args: &serde_json::Value) -> anyhow::Result<views::ActionView> {
+    Ok(views::ActionView::FunctionCall {
+        method_name: args["method_name"]
+            .as_str()
+            .context("`method_name` expected to be a string")?
+            .to_string(),
+        args: base64::decode(
+            args["args_base64"]
+                .as_str()
+                .context("`args_base64` expected to be a string")?,
+        )?
+        .into(),
+        gas: args["gas"].as_u64().context("`gas` expected to be u64")?,
+        deposit: args["deposit"]
+            .as_str()
+            .context("`deposit` expected to be a string")?
+            .parse()
+            .context("`deposit` expected to be u128")?,
+    })
+}
+
+/// Builds the shards in the same shape the indexer gets them from NEAR Lake,
+/// so the events get exactly the same numbering inside the shard.
+/// The shards having the outcomes without logs are skipped, the events of these outcomes are unknown,
+/// so the numbering of the other events in the shard can't be restored.
+/// Returns the shards and the number of the skipped ones
+#[allow(clippy::type_complexity)]
+fn group_by_block_and_shard(
+    stored_outcomes: Vec<StoredOutcome>,
+    function_calls: &mut HashMap<String, Vec<views::ActionView>>,
+) -> anyhow::Result<(
+    Vec<((u64, u64), near_indexer_primitives::IndexerShard)>,
+    usize,
+)> {
+    let mut grouped_outcomes: Vec<((u64, u64, u64), Vec<StoredOutcome>)> = Vec::new();
+    for stored_outcome in stored_outcomes {
+        let block_height = to_u64(&stored_outcome.block_height, "block_height")?;
+        let block_timestamp = to_u64(&stored_outcome.block_timestamp, "block_timestamp")?;
+        let shard_id = to_u64(&stored_outcome.shard_id, "shard_id")?;
+        match grouped_outcomes.last_mut() {
+            Some(((last_block_height, _, last_shard_id), shard_outcomes))
+                if *last_block_height == block_height && *last_shard_id == shard_id =>
+            {
+                shard_outcomes.push(stored_outcome);
+            }
+            _ => grouped_outcomes.push((
+                (block_height, block_timestamp, shard_id),
+                vec![stored_outcome],
+            )),
+        }
+    }
+
+    let mut shards = Vec::new();
+    let mut incomplete_shards_count = 0;
+    for ((block_height, block_timestamp, shard_id), shard_outcomes) in grouped_outcomes {
+        if shard_outcomes
+            .iter()
+            .any(|stored_outcome| stored_outcome.logs.is_none())
+        {
+            incomplete_shards_count += 1;
+            continue;
+        }
+        let receipt_execution_outcomes = shard_outcomes
+            .into_iter()
+            .map(|stored_outcome| {
+                let actions = function_calls
+                    .remove(&stored_outcome.receipt_id)
+                    .unwrap_or_default();
+                restore_outcome(stored_outcome, actions)
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        shards.push((
+            (block_height, block_timestamp),
+            near_indexer_primitives::IndexerShard {
+                shard_id,
+                chunk: None,
+                receipt_execution_outcomes,
+                state_changes: vec![],
+            },
+        ));
+    }
+    Ok((shards, incomplete_shards_count))
+}
+
+/// Restores only the data the events extraction relies on.
+/// The outcomes are stored only for action receipts, only the function call actions are restored.
+/// The outcome is expected to have the logs stored
+fn restore_outcome(
+    stored_outcome: StoredOutcome,
+    actions: Vec<views::ActionView>,
+) -> anyhow::Result<near_indexer_primitives::IndexerExecutionOutcomeWithReceipt> {
+    let logs = stored_outcome
+        .logs
+        .context("Execution outcome expected to have logs stored")?;
+    let receipt_id = near_indexer_primitives::CryptoHash::from_str(&stored_outcome.receipt_id)
+        .map_err(|err| anyhow::anyhow!("Failed to parse receipt_id: {}", err))?;
+
+    Ok(
+        near_indexer_primitives::IndexerExecutionOutcomeWithReceipt {
+            execution_outcome: views::ExecutionOutcomeWithIdView {
+                proof: vec![],
+                block_hash: near_indexer_primitives::CryptoHash::from_str(
+                    &stored_outcome.executed_in_block_hash,
+                )
+                .map_err(|err| anyhow::anyhow!("Failed to parse block hash: {}", err))?,
+                id: receipt_id,
+                outcome: views::ExecutionOutcomeView {
+                    logs,
+                    receipt_ids: vec![],
+                    gas_burnt: to_u64(&stored_outcome.gas_burnt, "gas_burnt")?,
+                    tokens_burnt: to_u128(&stored_outcome.tokens_burnt, "tokens_burnt")?,
+                    executor_id: stored_outcome.executor_account_id.parse()?,
+                    status: restore_status(
+                        &stored_outcome.status,
+                        stored_outcome.return_value_base64.as_deref(),
+                        stored_outcome.failure,
+                    ),
+                    metadata: views::ExecutionMetadataView {
+                        version: 1,
+                        gas_profile: None,
+                    },
+                },
+            },
+            receipt: views::ReceiptView {
+                predecessor_id: stored_outcome.predecessor_account_id.parse()?,
+                receiver_id: stored_outcome.receiver_account_id.parse()?,
+                receipt_id,
+                receipt: views::ReceiptEnumView::Action {
+                    signer_id: stored_outcome.signer_account_id.parse()?,
+                    signer_public_key: near_crypto::PublicKey::from_str(
+                        &stored_outcome.signer_public_key,
+                    )?,
+                    gas_price: to_u128(&stored_outcome.gas_price, "gas_price")?,
+                    output_data_receivers: vec![],
+                    input_data_ids: vec![],
+                    actions,
+                },
+            },
+        },
+    )
+}
+
+/// The outcomes indexed before the return value and the failure details were stored
+/// get empty return value and `Unknown` status instead of the failure
+fn restore_status(
+    status: &ExecutionOutcomeStatus,
+    return_value_base64: Option<&str>,
+    failure: Option<serde_json::Value>,
+) -> views::ExecutionStatusView {
+    match status {
+        ExecutionOutcomeStatus::SuccessValue => views::ExecutionStatusView::SuccessValue(
+            return_value_base64
+                .and_then(|return_value_base64| base64::decode(return_value_base64).ok())
+                .unwrap_or_default(),
+        ),
+        ExecutionOutcomeStatus::SuccessReceiptId => {
+            views::ExecutionStatusView::SuccessReceiptId(Default::default())
+        }
+        ExecutionOutcomeStatus::Failure => failure
+            .and_then(|failure| serde_json::from_value(failure).ok())
+            .map(views::ExecutionStatusView::Failure)
+            .unwrap_or(views::ExecutionStatusView::Unknown),
+        ExecutionOutcomeStatus::Unknown => views::ExecutionStatusView::Unknown,
+    }
+}
+
+fn to_u64(value: &BigDecimal, column_name: &str) -> anyhow::Result<u64> {
+    value
+        .to_u64()
+        .with_context(|| format!("`{}` expected to be u64", column_name))
+}
+
+fn to_u128(value: &BigDecimal, column_name: &str) -> anyhow::Result<u128> {
+    value
+        .to_string()
+        .parse()
+        .with_context(|| format!("`{}` expected to be u128", column_name))
+}
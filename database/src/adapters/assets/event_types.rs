@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 pub(crate) enum NearEvent {
     Nep141(Nep141Event),
     Nep171(Nep171Event),
+    Nep245(Nep245Event),
 }
 
 // *** NEP-141 FT ***
@@ -89,3 +90,48 @@ pub(crate) struct NftBurnData {
     pub token_ids: Vec<String>,
     pub memo: Option<String>,
 }
+
+// *** NEP-245 MT ***
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct Nep245Event {
+    pub version: String,
+    #[serde(flatten)]
+    pub event_kind: Nep245EventKind,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
+pub(crate) enum Nep245EventKind {
+    MtMint(Vec<MtMintData>),
+    MtTransfer(Vec<MtTransferData>),
+    MtBurn(Vec<MtBurnData>),
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct MtMintData {
+    pub owner_id: String,
+    pub token_ids: Vec<String>,
+    pub amounts: Vec<String>,
+    pub memo: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct MtTransferData {
+    pub authorized_id: Option<String>,
+    pub old_owner_id: String,
+    pub new_owner_id: String,
+    pub token_ids: Vec<String>,
+    pub amounts: Vec<String>,
+    pub memo: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct MtBurnData {
+    pub authorized_id: Option<String>,
+    pub owner_id: String,
+    pub token_ids: Vec<String>,
+    pub amounts: Vec<String>,
+    pub memo: Option<String>,
+}
@@ -3,15 +3,37 @@ use diesel::PgConnection;
 use tracing::warn;
 
 use crate::adapters::assets;
+use crate::models;
 
 use super::event_types;
+use super::validation::ShardEventLines;
+
+/// Switches of the events extraction
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EventsOptions {
+    /// Store malformed event entries to `assets__invalid_events` instead of the assets tables
+    pub strict_events_validation: bool,
+    /// Restore FT transfers from the function calls of the contracts without NEP-141 events
+    #[cfg(feature = "fungible_token_events")]
+    pub detect_legacy_ft_transfers: bool,
+}
 
 pub async fn store_events(
     pool: &Database<PgConnection>,
     streamer_message: &near_indexer_primitives::StreamerMessage,
+    contracts_filter_arc: &assets::filter::ContractsFilterArc,
+    options: EventsOptions,
 ) -> anyhow::Result<()> {
+    // Taking a snapshot of the rules to avoid holding the lock while we are writing to the database
+    let contracts_filter = contracts_filter_arc.read().await.clone();
     let futures = streamer_message.shards.iter().map(|shard| {
-        collect_and_store_events(pool, shard, streamer_message.block.header.timestamp)
+        collect_and_store_events(
+            pool,
+            shard,
+            streamer_message.block.header.timestamp,
+            &contracts_filter,
+            options,
+        )
     });
 
     futures::future::try_join_all(futures).await.map(|_| ())
@@ -41,17 +63,69 @@ pub(crate) async fn detect_db_error(
     false
 }
 
+/// The lines of the assets tables composed for one shard
+pub(super) struct ShardEvents {
+    #[cfg(feature = "fungible_token_events")]
+    pub ft_events: ShardEventLines<models::assets::fungible_token_events::FungibleTokenEvent>,
+    pub nft_events:
+        ShardEventLines<models::assets::non_fungible_token_events::NonFungibleTokenEvent>,
+    pub mt_events: ShardEventLines<models::assets::multi_token_events::MultiTokenEvent>,
+}
+
 async fn collect_and_store_events(
     pool: &Database<PgConnection>,
     shard: &near_indexer_primitives::IndexerShard,
     block_timestamp: u64,
+    contracts_filter: &assets::filter::ContractsFilter,
+    options: EventsOptions,
 ) -> anyhow::Result<()> {
+    let shard_events = collect_events(shard, block_timestamp, contracts_filter, options);
+
+    #[cfg(feature = "fungible_token_events")]
+    let ft_future = assets::fungible_token_events::store_ft_events(pool, &shard_events.ft_events);
+    let nft_future =
+        assets::non_fungible_token_events::store_nft_events(pool, &shard_events.nft_events);
+    let mt_future = assets::multi_token_events::store_mt_events(pool, &shard_events.mt_events);
+    #[cfg(feature = "fungible_token_events")]
+    futures::try_join!(ft_future, nft_future, mt_future)?;
+    #[cfg(not(feature = "fungible_token_events"))]
+    futures::try_join!(nft_future, mt_future)?;
+    Ok(())
+}
+
+/// Extracts the events of the shard and numbers them the way they are stored
+pub(super) fn collect_events(
+    shard: &near_indexer_primitives::IndexerShard,
+    block_timestamp: u64,
+    contracts_filter: &assets::filter::ContractsFilter,
+    options: EventsOptions,
+) -> ShardEvents {
     #[cfg(feature = "fungible_token_events")]
     let mut ft_events_with_outcomes = Vec::new();
+    #[cfg(feature = "fungible_token_events")]
+    let mut legacy_ft_transfers_with_outcomes = Vec::new();
     let mut nft_events_with_outcomes = Vec::new();
+    let mut mt_events_with_outcomes = Vec::new();
 
-    for outcome in &shard.receipt_execution_outcomes {
+    for outcome in shard
+        .receipt_execution_outcomes
+        .iter()
+        .filter(|outcome| contracts_filter.is_allowed(outcome.receipt.receiver_id.as_str()))
+    {
         let events = extract_events(outcome);
+        #[cfg(feature = "fungible_token_events")]
+        if options.detect_legacy_ft_transfers
+            && contracts_filter.is_legacy_ft_contract(outcome.receipt.receiver_id.as_str())
+            && !events
+                .iter()
+                .any(|event| matches!(event, assets::event_types::NearEvent::Nep141(_)))
+        {
+            legacy_ft_transfers_with_outcomes.extend(
+                assets::legacy_ft_transfers::detect_legacy_ft_transfers(outcome)
+                    .into_iter()
+                    .map(|transfer| (transfer, outcome)),
+            );
+        }
         for event in events {
             match event {
                 #[cfg(feature = "fungible_token_events")]
@@ -61,30 +135,37 @@ async fn collect_and_store_events(
                 assets::event_types::NearEvent::Nep171(nft_event) => {
                     nft_events_with_outcomes.push((nft_event, outcome));
                 }
+                assets::event_types::NearEvent::Nep245(mt_event) => {
+                    mt_events_with_outcomes.push((mt_event, outcome));
+                }
                 #[cfg(not(feature = "fungible_token_events"))]
                 _ => (),
             }
         }
     }
 
-    #[cfg(feature = "fungible_token_events")]
-    let ft_future = assets::fungible_token_events::store_ft_events(
-        pool,
-        shard,
-        block_timestamp,
-        &ft_events_with_outcomes,
-    );
-    let nft_future = assets::non_fungible_token_events::store_nft_events(
-        pool,
-        shard,
-        block_timestamp,
-        &nft_events_with_outcomes,
-    );
-    #[cfg(feature = "fungible_token_events")]
-    futures::try_join!(ft_future, nft_future)?;
-    #[cfg(not(feature = "fungible_token_events"))]
-    futures::try_join!(nft_future)?;
-    Ok(())
+    ShardEvents {
+        #[cfg(feature = "fungible_token_events")]
+        ft_events: assets::fungible_token_events::compose_ft_db_events(
+            &ft_events_with_outcomes,
+            &legacy_ft_transfers_with_outcomes,
+            block_timestamp,
+            &shard.shard_id,
+            options.strict_events_validation,
+        ),
+        nft_events: assets::non_fungible_token_events::compose_nft_db_events(
+            &nft_events_with_outcomes,
+            block_timestamp,
+            &shard.shard_id,
+            options.strict_events_validation,
+        ),
+        mt_events: assets::multi_token_events::compose_mt_db_events(
+            &mt_events_with_outcomes,
+            block_timestamp,
+            &shard.shard_id,
+            options.strict_events_validation,
+        ),
+    }
 }
 
 fn extract_events(
@@ -113,3 +194,43 @@ fn extract_events(
         }
     }).collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_events_parses_event_json_logs_only() {
+        let outcome = crate::adapters::test_utils::function_call_outcome(
+            "alice.near",
+            "mt.near",
+            vec![],
+            vec![
+                "Transfer 10 from alice.near to bob.near",
+                r#"  EVENT_JSON: {"standard":"nep245","version":"1.0.0","event":"mt_burn","data":[{"owner_id":"alice.near","token_ids":["1"],"amounts":["10"]}]}"#,
+                r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_mint","data":[{"owner_id":"alice.near","token_ids":["1"]}]}"#,
+                r#"EVENT_JSON:{"standard":"nep245","version":"1.0.0","event":"mt_unknown","data":[]}"#,
+                "EVENT_JSON:not a json",
+            ],
+            near_indexer_primitives::views::ExecutionStatusView::SuccessValue(vec![]),
+        );
+
+        let events = extract_events(&outcome);
+
+        assert_eq!(events.len(), 2);
+        match &events[0] {
+            event_types::NearEvent::Nep245(mt_event) => match &mt_event.event_kind {
+                event_types::Nep245EventKind::MtBurn(burn_events) => {
+                    assert_eq!(burn_events.len(), 1);
+                    assert_eq!(burn_events[0].owner_id, "alice.near");
+                    assert_eq!(burn_events[0].token_ids, vec!["1".to_string()]);
+                    assert_eq!(burn_events[0].amounts, vec!["10".to_string()]);
+                    assert_eq!(burn_events[0].authorized_id, None);
+                }
+                other => panic!("`mt_burn` expected, got {:?}", other),
+            },
+            other => panic!("NEP-245 event expected, got {:?}", other),
+        }
+        assert!(matches!(events[1], event_types::NearEvent::Nep171(_)));
+    }
+}
@@ -0,0 +1,223 @@
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use anyhow::Context;
+use serde::Deserialize;
+use tracing::{info, warn};
+
+const RELOAD_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+// The rules are shared between the indexing process and the task watching for the rules file changes
+pub type ContractsFilterArc = std::sync::Arc<tokio::sync::RwLock<ContractsFilter>>;
+
+/// Defines which contracts' events should be stored to the assets tables.
+/// Expected to be provided as a JSON file:
+///
+/// ```json
+/// {
+///     "allowlist": [],
+///     "denylist": ["*.spam.near", "scam-nft.near"],
+///     "legacy_ft_contracts": ["wrap.near", "*.factory.bridge.near"]
+/// }
+/// ```
+///
+/// Empty `allowlist` means all the contracts are allowed.
+/// `denylist` has the priority over `allowlist`.
+/// `legacy_ft_contracts` are the known NEP-141 contracts the transfers are restored for
+/// with `--detect-legacy-ft-transfers`, the function calls of the other contracts are not trusted.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct ContractsFilter {
+    #[serde(default)]
+    pub allowlist: Vec<ContractPattern>,
+    #[serde(default)]
+    pub denylist: Vec<ContractPattern>,
+    #[serde(default)]
+    pub legacy_ft_contracts: Vec<ContractPattern>,
+}
+
+impl ContractsFilter {
+    pub fn from_file(path: &std::path::Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read assets filter file {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse assets filter file {}", path.display()))
+    }
+
+    /// Checks whether the events emitted by the given contract should be stored
+    pub fn is_allowed(&self, contract_account_id: &str) -> bool {
+        if self
+            .denylist
+            .iter()
+            .any(|pattern| pattern.matches(contract_account_id))
+        {
+            return false;
+        }
+        self.allowlist.is_empty()
+            || self
+                .allowlist
+                .iter()
+                .any(|pattern| pattern.matches(contract_account_id))
+    }
+
+    /// Checks whether the FT transfers of the given contract can be restored from its function calls
+    pub fn is_legacy_ft_contract(&self, contract_account_id: &str) -> bool {
+        self.is_allowed(contract_account_id)
+            && self
+                .legacy_ft_contracts
+                .iter()
+                .any(|pattern| pattern.matches(contract_account_id))
+    }
+}
+
+/// Contract account pattern, either the exact account name (`scam-nft.near`)
+/// or the suffix starting with `*` (`*.spam.near`)
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(try_from = "String")]
+pub enum ContractPattern {
+    Exact(String),
+    Suffix(String),
+}
+
+impl ContractPattern {
+    pub fn matches(&self, contract_account_id: &str) -> bool {
+        match self {
+            Self::Exact(account_id) => account_id == contract_account_id,
+            Self::Suffix(suffix) => contract_account_id.ends_with(suffix.as_str()),
+        }
+    }
+}
+
+impl FromStr for ContractPattern {
+    type Err = anyhow::Error;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            anyhow::bail!("Contract pattern can't be empty");
+        }
+        match pattern.strip_prefix('*') {
+            Some("") => anyhow::bail!(
+                "Contract pattern `*` matches everything, use an empty allowlist instead"
+            ),
+            Some(suffix) => Ok(Self::Suffix(suffix.to_string())),
+            None => Ok(Self::Exact(pattern.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for ContractPattern {
+    type Error = anyhow::Error;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        Self::from_str(&pattern)
+    }
+}
+
+/// Watches the filter file and reloads the rules once the file is modified,
+/// so the rules can be changed without restarting the indexer.
+/// In case of broken file, the previous rules are kept.
+pub async fn watch_contracts_filter_file(
+    path: std::path::PathBuf,
+    contracts_filter_arc: ContractsFilterArc,
+) {
+    let mut last_modified = get_modification_time(&path);
+    loop {
+        tokio::time::sleep(RELOAD_CHECK_INTERVAL).await;
+
+        let modified = get_modification_time(&path);
+        if modified == last_modified {
+            continue;
+        }
+        last_modified = modified;
+
+        match ContractsFilter::from_file(&path) {
+            Ok(contracts_filter) => {
+                info!(
+                    target: crate::EXPLORER_DATABASE,
+                    "Assets filter is reloaded from {}: {} allowed, {} denied and {} legacy FT patterns",
+                    path.display(),
+                    contracts_filter.allowlist.len(),
+                    contracts_filter.denylist.len(),
+                    contracts_filter.legacy_ft_contracts.len(),
+                );
+                *contracts_filter_arc.write().await = contracts_filter;
+            }
+            Err(err) => {
+                warn!(
+                    target: crate::EXPLORER_DATABASE,
+                    "Keeping previous assets filter rules: {:#}", err,
+                );
+            }
+        }
+    }
+}
+
+fn get_modification_time(path: &std::path::Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contract_pattern_parsing() {
+        assert_eq!(
+            ContractPattern::from_str(" scam-nft.near ").unwrap(),
+            ContractPattern::Exact("scam-nft.near".to_string())
+        );
+        assert_eq!(
+            ContractPattern::from_str("*.spam.near").unwrap(),
+            ContractPattern::Suffix(".spam.near".to_string())
+        );
+        assert!(ContractPattern::from_str("").is_err());
+        assert!(ContractPattern::from_str("*").is_err());
+    }
+
+    #[test]
+    fn test_contract_pattern_matching() {
+        let exact = ContractPattern::from_str("scam-nft.near").unwrap();
+        assert!(exact.matches("scam-nft.near"));
+        assert!(!exact.matches("not-scam-nft.near"));
+
+        let suffix = ContractPattern::from_str("*.spam.near").unwrap();
+        assert!(suffix.matches("nft.spam.near"));
+        assert!(suffix.matches("a.b.spam.near"));
+        assert!(!suffix.matches("spam.near"));
+        assert!(!suffix.matches("nft.spam.near.org"));
+    }
+
+    #[test]
+    fn test_denylist_has_priority_over_allowlist() {
+        let contracts_filter: ContractsFilter = serde_json::from_str(
+            r#"{
+                "allowlist": ["*.near"],
+                "denylist": ["*.spam.near"],
+                "legacy_ft_contracts": ["wrap.near", "old-token.spam.near"]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(contracts_filter.is_allowed("nft.near"));
+        assert!(!contracts_filter.is_allowed("nft.spam.near"));
+        assert!(!contracts_filter.is_allowed("nft.testnet"));
+
+        assert!(contracts_filter.is_legacy_ft_contract("wrap.near"));
+        assert!(!contracts_filter.is_legacy_ft_contract("usdt.near"));
+        assert!(!contracts_filter.is_legacy_ft_contract("old-token.spam.near"));
+    }
+
+    #[test]
+    fn test_empty_filter_allows_everything() {
+        let contracts_filter: ContractsFilter = serde_json::from_str("{}").unwrap();
+        assert!(contracts_filter.is_allowed("nft.near"));
+        assert!(!contracts_filter.is_legacy_ft_contract("wrap.near"));
+    }
+
+    #[test]
+    fn test_broken_pattern_fails_the_whole_filter() {
+        assert!(serde_json::from_str::<ContractsFilter>(r#"{"denylist": ["*"]}"#).is_err());
+    }
+}
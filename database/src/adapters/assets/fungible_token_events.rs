@@ -1,6 +1,5 @@
 use actix_diesel::dsl::AsyncRunQueryDsl;
 use actix_diesel::{AsyncError, Database};
-use bigdecimal::BigDecimal;
 use diesel::PgConnection;
 
 use crate::adapters::{assets, CHUNK_SIZE_FOR_BATCH_INSERT};
@@ -8,19 +7,13 @@ use crate::models;
 use crate::schema;
 
 use super::event_types;
+use super::validation::ShardEventLines;
 
 pub(crate) async fn store_ft_events(
     pool: &Database<PgConnection>,
-    shard: &near_indexer_primitives::IndexerShard,
-    block_timestamp: u64,
-    events_with_outcomes: &[(
-        assets::event_types::Nep141Event,
-        &near_indexer_primitives::IndexerExecutionOutcomeWithReceipt,
-    )],
+    ft_events: &ShardEventLines<models::assets::fungible_token_events::FungibleTokenEvent>,
 ) -> anyhow::Result<()> {
-    let ft_events = compose_ft_db_events(events_with_outcomes, block_timestamp, &shard.shard_id);
-
-    for chunk in ft_events.chunks(CHUNK_SIZE_FOR_BATCH_INSERT) {
+    for chunk in ft_events.valid.chunks(CHUNK_SIZE_FOR_BATCH_INSERT) {
         let ft_events_chunk = chunk.to_owned();
         crate::await_retry_or_panic!(
             diesel::insert_into(schema::assets__fungible_token_events::table)
@@ -33,7 +26,7 @@ pub(crate) async fn store_ft_events(
         );
     }
 
-    Ok(())
+    assets::invalid_events::store_invalid_events(pool, &ft_events.invalid).await
 }
 
 async fn detect_ft_db_error(async_error: &AsyncError<diesel::result::Error>) -> bool {
@@ -45,94 +38,167 @@ async fn detect_ft_db_error(async_error: &AsyncError<diesel::result::Error>) ->
     .await
 }
 
-fn compose_ft_db_events(
+pub(crate) fn compose_ft_db_events(
     events_with_outcomes: &[(
         assets::event_types::Nep141Event,
         &near_indexer_primitives::IndexerExecutionOutcomeWithReceipt,
     )],
+    legacy_transfers_with_outcomes: &[(
+        assets::legacy_ft_transfers::LegacyFtTransfer,
+        &near_indexer_primitives::IndexerExecutionOutcomeWithReceipt,
+    )],
     block_timestamp: u64,
     shard_id: &near_indexer_primitives::types::ShardId,
-) -> Vec<models::assets::fungible_token_events::FungibleTokenEvent> {
-    let mut ft_events = Vec::new();
+    strict_events_validation: bool,
+) -> ShardEventLines<models::assets::fungible_token_events::FungibleTokenEvent> {
+    let mut ft_events = ShardEventLines::new(strict_events_validation, block_timestamp, *shard_id);
+    compose_nep141_db_events(&mut ft_events, events_with_outcomes);
+    // Continuing the numbering, so the derived lines go after the real events in the shard
+    compose_legacy_ft_db_events(&mut ft_events, legacy_transfers_with_outcomes);
+    ft_events
+}
+
+fn compose_nep141_db_events(
+    ft_events: &mut ShardEventLines<models::assets::fungible_token_events::FungibleTokenEvent>,
+    events_with_outcomes: &[(
+        assets::event_types::Nep141Event,
+        &near_indexer_primitives::IndexerExecutionOutcomeWithReceipt,
+    )],
+) {
     for (event, outcome) in events_with_outcomes {
         let contract_id = &outcome.receipt.receiver_id;
         match &event.event_kind {
             event_types::Nep141EventKind::FtMint(mint_events) => {
                 for mint_event in mint_events {
-                    ft_events.push(models::assets::fungible_token_events::FungibleTokenEvent {
-                        emitted_for_receipt_id: outcome.receipt.receipt_id.to_string(),
-                        emitted_at_block_timestamp: BigDecimal::from(block_timestamp),
-                        emitted_in_shard_id: BigDecimal::from(*shard_id),
-                        emitted_index_of_event_entry_in_shard: ft_events.len() as i32,
-                        emitted_by_contract_account_id: contract_id.to_string(),
-                        amount: mint_event.amount.to_string(),
-                        event_kind: models::enums::FtEventKind::Mint,
-                        token_old_owner_account_id: "".to_string(),
-                        token_new_owner_account_id: mint_event
-                            .owner_id
-                            .escape_default()
-                            .to_string(),
-                        event_memo: mint_event
-                            .memo
-                            .clone()
-                            .unwrap_or_default()
-                            .escape_default()
-                            .to_string(),
-                    });
+                    if !ft_events.accept(mint_event, outcome) {
+                        continue;
+                    }
+                    let index_in_shard = ft_events.next_index();
+                    ft_events.valid.push(
+                        models::assets::fungible_token_events::FungibleTokenEvent {
+                            emitted_for_receipt_id: outcome.receipt.receipt_id.to_string(),
+                            emitted_at_block_timestamp: ft_events.block_timestamp(),
+                            emitted_in_shard_id: ft_events.shard_id(),
+                            emitted_index_of_event_entry_in_shard: index_in_shard,
+                            emitted_by_contract_account_id: contract_id.to_string(),
+                            amount: mint_event.amount.to_string(),
+                            event_kind: models::enums::FtEventKind::Mint,
+                            token_old_owner_account_id: "".to_string(),
+                            token_new_owner_account_id: mint_event
+                                .owner_id
+                                .escape_default()
+                                .to_string(),
+                            event_memo: mint_event
+                                .memo
+                                .clone()
+                                .unwrap_or_default()
+                                .escape_default()
+                                .to_string(),
+                            derived_from_function_call: false,
+                        },
+                    );
                 }
             }
             event_types::Nep141EventKind::FtTransfer(transfer_events) => {
                 for transfer_event in transfer_events {
-                    ft_events.push(models::assets::fungible_token_events::FungibleTokenEvent {
-                        emitted_for_receipt_id: outcome.receipt.receipt_id.to_string(),
-                        emitted_at_block_timestamp: BigDecimal::from(block_timestamp),
-                        emitted_in_shard_id: BigDecimal::from(*shard_id),
-                        emitted_index_of_event_entry_in_shard: ft_events.len() as i32,
-                        emitted_by_contract_account_id: contract_id.to_string(),
-                        amount: transfer_event.amount.to_string(),
-                        event_kind: models::enums::FtEventKind::Transfer,
-                        token_old_owner_account_id: transfer_event
-                            .old_owner_id
-                            .escape_default()
-                            .to_string(),
-                        token_new_owner_account_id: transfer_event
-                            .new_owner_id
-                            .escape_default()
-                            .to_string(),
-                        event_memo: transfer_event
-                            .memo
-                            .clone()
-                            .unwrap_or_default()
-                            .escape_default()
-                            .to_string(),
-                    });
+                    if !ft_events.accept(transfer_event, outcome) {
+                        continue;
+                    }
+                    let index_in_shard = ft_events.next_index();
+                    ft_events.valid.push(
+                        models::assets::fungible_token_events::FungibleTokenEvent {
+                            emitted_for_receipt_id: outcome.receipt.receipt_id.to_string(),
+                            emitted_at_block_timestamp: ft_events.block_timestamp(),
+                            emitted_in_shard_id: ft_events.shard_id(),
+                            emitted_index_of_event_entry_in_shard: index_in_shard,
+                            emitted_by_contract_account_id: contract_id.to_string(),
+                            amount: transfer_event.amount.to_string(),
+                            event_kind: models::enums::FtEventKind::Transfer,
+                            token_old_owner_account_id: transfer_event
+                                .old_owner_id
+                                .escape_default()
+                                .to_string(),
+                            token_new_owner_account_id: transfer_event
+                                .new_owner_id
+                                .escape_default()
+                                .to_string(),
+                            event_memo: transfer_event
+                                .memo
+                                .clone()
+                                .unwrap_or_default()
+                                .escape_default()
+                                .to_string(),
+                            derived_from_function_call: false,
+                        },
+                    );
                 }
             }
             event_types::Nep141EventKind::FtBurn(burn_events) => {
                 for burn_event in burn_events {
-                    ft_events.push(models::assets::fungible_token_events::FungibleTokenEvent {
-                        emitted_for_receipt_id: outcome.receipt.receipt_id.to_string(),
-                        emitted_at_block_timestamp: BigDecimal::from(block_timestamp),
-                        emitted_in_shard_id: BigDecimal::from(*shard_id),
-                        emitted_index_of_event_entry_in_shard: ft_events.len() as i32,
-                        emitted_by_contract_account_id: contract_id.to_string(),
-                        amount: burn_event.amount.to_string(),
-                        event_kind: models::enums::FtEventKind::Burn,
-                        token_old_owner_account_id: burn_event
-                            .owner_id
-                            .escape_default()
-                            .to_string(),
-                        token_new_owner_account_id: "".to_string(),
-                        event_memo: burn_event
-                            .memo
-                            .clone()
-                            .unwrap_or_default()
-                            .escape_default()
-                            .to_string(),
-                    });
+                    if !ft_events.accept(burn_event, outcome) {
+                        continue;
+                    }
+                    let index_in_shard = ft_events.next_index();
+                    ft_events.valid.push(
+                        models::assets::fungible_token_events::FungibleTokenEvent {
+                            emitted_for_receipt_id: outcome.receipt.receipt_id.to_string(),
+                            emitted_at_block_timestamp: ft_events.block_timestamp(),
+                            emitted_in_shard_id: ft_events.shard_id(),
+                            emitted_index_of_event_entry_in_shard: index_in_shard,
+                            emitted_by_contract_account_id: contract_id.to_string(),
+                            amount: burn_event.amount.to_string(),
+                            event_kind: models::enums::FtEventKind::Burn,
+                            token_old_owner_account_id: burn_event
+                                .owner_id
+                                .escape_default()
+                                .to_string(),
+                            token_new_owner_account_id: "".to_string(),
+                            event_memo: burn_event
+                                .memo
+                                .clone()
+                                .unwrap_or_default()
+                                .escape_default()
+                                .to_string(),
+                            derived_from_function_call: false,
+                        },
+                    );
                 }
             }
         }
     }
-    ft_events
+}
+
+fn compose_legacy_ft_db_events(
+    ft_events: &mut ShardEventLines<models::assets::fungible_token_events::FungibleTokenEvent>,
+    legacy_transfers_with_outcomes: &[(
+        assets::legacy_ft_transfers::LegacyFtTransfer,
+        &near_indexer_primitives::IndexerExecutionOutcomeWithReceipt,
+    )],
+) {
+    for (transfer, outcome) in legacy_transfers_with_outcomes {
+        if !ft_events.accept(transfer, outcome) {
+            continue;
+        }
+        let index_in_shard = ft_events.next_index();
+        ft_events
+            .valid
+            .push(models::assets::fungible_token_events::FungibleTokenEvent {
+                emitted_for_receipt_id: outcome.receipt.receipt_id.to_string(),
+                emitted_at_block_timestamp: ft_events.block_timestamp(),
+                emitted_in_shard_id: ft_events.shard_id(),
+                emitted_index_of_event_entry_in_shard: index_in_shard,
+                emitted_by_contract_account_id: outcome.receipt.receiver_id.to_string(),
+                amount: transfer.amount.to_string(),
+                event_kind: models::enums::FtEventKind::Transfer,
+                token_old_owner_account_id: transfer.old_owner_id.escape_default().to_string(),
+                token_new_owner_account_id: transfer.new_owner_id.escape_default().to_string(),
+                event_memo: transfer
+                    .memo
+                    .clone()
+                    .unwrap_or_default()
+                    .escape_default()
+                    .to_string(),
+                derived_from_function_call: true,
+            });
+    }
 }
@@ -0,0 +1,38 @@
+use actix_diesel::dsl::AsyncRunQueryDsl;
+use actix_diesel::{AsyncError, Database};
+use diesel::PgConnection;
+
+use crate::adapters::{assets, CHUNK_SIZE_FOR_BATCH_INSERT};
+use crate::models;
+use crate::schema;
+
+/// The lines are composed together with the valid lines of the same standard,
+/// see `validation::ShardEventLines`
+pub(crate) async fn store_invalid_events(
+    pool: &Database<PgConnection>,
+    invalid_events: &[models::assets::invalid_events::InvalidEvent],
+) -> anyhow::Result<()> {
+    for chunk in invalid_events.chunks(CHUNK_SIZE_FOR_BATCH_INSERT) {
+        let invalid_events_chunk = chunk.to_owned();
+        crate::await_retry_or_panic!(
+            diesel::insert_into(schema::assets__invalid_events::table)
+                .values(invalid_events_chunk.clone())
+                .execute_async(pool),
+            10,
+            "InvalidEvent were adding to database".to_string(),
+            &invalid_events_chunk,
+            detect_invalid_events_db_error
+        );
+    }
+
+    Ok(())
+}
+
+async fn detect_invalid_events_db_error(async_error: &AsyncError<diesel::result::Error>) -> bool {
+    assets::events::detect_db_error(
+        async_error,
+        "assets__invalid_events_pkey",
+        "assets__invalid_events_unique",
+    )
+    .await
+}
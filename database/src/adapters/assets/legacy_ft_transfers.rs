@@ -0,0 +1,228 @@
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use near_indexer_primitives::views::{ActionView, ExecutionStatusView, ReceiptEnumView};
+
+/// FT transfer restored from the function call arguments.
+/// Used for NEP-141 contracts which do not emit `EVENT_JSON` logs
+#[derive(Serialize, Debug)]
+pub(crate) struct LegacyFtTransfer {
+    pub old_owner_id: String,
+    pub new_owner_id: String,
+    pub amount: String,
+    pub memo: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct FtTransferArgs {
+    receiver_id: String,
+    amount: String,
+    memo: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct FtResolveTransferArgs {
+    sender_id: String,
+    receiver_id: String,
+    amount: String,
+}
+
+/// NEP-141 requires exactly 1 yoctoNEAR attached to `ft_transfer` and `ft_transfer_call`
+const FT_TRANSFER_DEPOSIT: near_indexer_primitives::types::Balance = 1;
+
+/// Restores FT transfers from `ft_transfer`, `ft_transfer_call` and `ft_resolve_transfer` calls.
+/// Should be called only for the outcomes without NEP-141 events of the known FT contracts
+/// (`legacy_ft_contracts` of the assets filter), otherwise the transfers will be doubled,
+/// or the calls of the unrelated contracts with the same method names will become transfers
+pub(crate) fn detect_legacy_ft_transfers(
+    outcome: &near_indexer_primitives::IndexerExecutionOutcomeWithReceipt,
+) -> Vec<LegacyFtTransfer> {
+    let actions = match &outcome.receipt.receipt {
+        ReceiptEnumView::Action { actions, .. } => actions,
+        ReceiptEnumView::Data { .. } => return vec![],
+    };
+    // The balances are changed only if the whole receipt succeeded
+    let return_value = match &outcome.execution_outcome.outcome.status {
+        ExecutionStatusView::SuccessValue(value) => Some(value),
+        ExecutionStatusView::SuccessReceiptId(_) => None,
+        ExecutionStatusView::Unknown | ExecutionStatusView::Failure(_) => return vec![],
+    };
+
+    let predecessor_id = outcome.receipt.predecessor_id.as_str();
+    let contract_id = outcome.receipt.receiver_id.as_str();
+    actions
+        .iter()
+        .filter_map(|action| match action {
+            ActionView::FunctionCall {
+                method_name,
+                args,
+                deposit,
+                ..
+            } => match method_name.as_str() {
+                "ft_transfer" | "ft_transfer_call" if *deposit == FT_TRANSFER_DEPOSIT => {
+                    let args: FtTransferArgs = serde_json::from_slice(args).ok()?;
+                    if args.amount.parse::<u128>().is_err() {
+                        return None;
+                    }
+                    Some(LegacyFtTransfer {
+                        old_owner_id: predecessor_id.to_string(),
+                        new_owner_id: args.receiver_id,
+                        amount: args.amount,
+                        memo: args.memo,
+                    })
+                }
+                // `ft_resolve_transfer` is a private method, the call from the other account
+                // would fail, but we don't want to rely on the contract implementation
+                "ft_resolve_transfer" if predecessor_id == contract_id => {
+                    let args: FtResolveTransferArgs = serde_json::from_slice(args).ok()?;
+                    compose_refund(args, return_value?, outcome)
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// `ft_resolve_transfer` returns the amount which was used by the receiver,
+/// the rest is returned back to the sender
+fn compose_refund(
+    args: FtResolveTransferArgs,
+    return_value: &[u8],
+    outcome: &near_indexer_primitives::IndexerExecutionOutcomeWithReceipt,
+) -> Option<LegacyFtTransfer> {
+    let amount = args.amount.parse::<u128>().ok()?;
+    let used_amount = match serde_json::from_slice::<String>(return_value)
+        .ok()
+        .and_then(|used_amount| used_amount.parse::<u128>().ok())
+    {
+        Some(used_amount) => used_amount,
+        None => {
+            warn!(
+                target: crate::EXPLORER_DATABASE,
+                "assets::legacy_ft_transfers: unexpected `ft_resolve_transfer` return value in receipt {}. Refund is ignored",
+                outcome.receipt.receipt_id,
+            );
+            return None;
+        }
+    };
+
+    let refund_amount = amount.checked_sub(used_amount)?;
+    if refund_amount == 0 {
+        return None;
+    }
+    Some(LegacyFtTransfer {
+        old_owner_id: args.receiver_id,
+        new_owner_id: args.sender_id,
+        amount: refund_amount.to_string(),
+        memo: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn function_call(method_name: &str, args: serde_json::Value, deposit: u128) -> ActionView {
+        ActionView::FunctionCall {
+            method_name: method_name.to_string(),
+            args: serde_json::to_vec(&args).unwrap().into(),
+            gas: 30_000_000_000_000,
+            deposit,
+        }
+    }
+
+    fn detect(
+        predecessor_id: &str,
+        action: ActionView,
+        status: ExecutionStatusView,
+    ) -> Vec<LegacyFtTransfer> {
+        let outcome = crate::adapters::test_utils::function_call_outcome(
+            predecessor_id,
+            "ft.near",
+            vec![action],
+            vec![],
+            status,
+        );
+        detect_legacy_ft_transfers(&outcome)
+    }
+
+    #[test]
+    fn test_ft_transfer_is_restored() {
+        let transfers = detect(
+            "alice.near",
+            function_call(
+                "ft_transfer",
+                serde_json::json!({"receiver_id": "bob.near", "amount": "100", "memo": "hi"}),
+                1,
+            ),
+            ExecutionStatusView::SuccessValue(vec![]),
+        );
+
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].old_owner_id, "alice.near");
+        assert_eq!(transfers[0].new_owner_id, "bob.near");
+        assert_eq!(transfers[0].amount, "100");
+        assert_eq!(transfers[0].memo.as_deref(), Some("hi"));
+    }
+
+    #[test]
+    fn test_ft_transfer_without_one_yocto_is_ignored() {
+        for deposit in [0, 2] {
+            let transfers = detect(
+                "alice.near",
+                function_call(
+                    "ft_transfer_call",
+                    serde_json::json!({"receiver_id": "bob.near", "amount": "100", "msg": ""}),
+                    deposit,
+                ),
+                ExecutionStatusView::SuccessReceiptId(Default::default()),
+            );
+            assert!(transfers.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_failed_ft_transfer_is_ignored() {
+        let transfers = detect(
+            "alice.near",
+            function_call(
+                "ft_transfer",
+                serde_json::json!({"receiver_id": "bob.near", "amount": "100"}),
+                1,
+            ),
+            ExecutionStatusView::Unknown,
+        );
+        assert!(transfers.is_empty());
+    }
+
+    #[test]
+    fn test_unused_amount_is_refunded() {
+        let args = serde_json::json!({"sender_id": "alice.near", "receiver_id": "dex.near", "amount": "100"});
+        let transfers = detect(
+            "ft.near",
+            function_call("ft_resolve_transfer", args.clone(), 0),
+            ExecutionStatusView::SuccessValue(b"\"30\"".to_vec()),
+        );
+
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].old_owner_id, "dex.near");
+        assert_eq!(transfers[0].new_owner_id, "alice.near");
+        assert_eq!(transfers[0].amount, "70");
+
+        // Everything is used
+        assert!(detect(
+            "ft.near",
+            function_call("ft_resolve_transfer", args.clone(), 0),
+            ExecutionStatusView::SuccessValue(b"\"100\"".to_vec()),
+        )
+        .is_empty());
+        // Not called by the contract itself
+        assert!(detect(
+            "alice.near",
+            function_call("ft_resolve_transfer", args, 0),
+            ExecutionStatusView::SuccessValue(b"\"30\"".to_vec()),
+        )
+        .is_empty());
+    }
+}
@@ -1,5 +1,12 @@
+pub mod backfill;
 mod event_types;
 pub mod events;
+pub mod filter;
 #[cfg(feature = "fungible_token_events")]
 pub(crate) mod fungible_token_events;
+pub(crate) mod invalid_events;
+#[cfg(feature = "fungible_token_events")]
+mod legacy_ft_transfers;
+pub(crate) mod multi_token_events;
 pub(crate) mod non_fungible_token_events;
+mod validation;
@@ -0,0 +1,292 @@
+use actix_diesel::dsl::AsyncRunQueryDsl;
+use actix_diesel::{AsyncError, Database};
+use diesel::PgConnection;
+use tracing::warn;
+
+use crate::adapters::{assets, CHUNK_SIZE_FOR_BATCH_INSERT};
+use crate::models;
+use crate::schema;
+
+use super::event_types;
+use super::validation::ShardEventLines;
+
+pub(crate) async fn store_mt_events(
+    pool: &Database<PgConnection>,
+    mt_events: &ShardEventLines<models::assets::multi_token_events::MultiTokenEvent>,
+) -> anyhow::Result<()> {
+    for chunk in mt_events.valid.chunks(CHUNK_SIZE_FOR_BATCH_INSERT) {
+        let mt_events_chunk = chunk.to_owned();
+        crate::await_retry_or_panic!(
+            diesel::insert_into(schema::assets__multi_token_events::table)
+                .values(mt_events_chunk.clone())
+                .execute_async(pool),
+            10,
+            "MultiTokenEvent were adding to database".to_string(),
+            &mt_events_chunk,
+            detect_mt_db_error
+        );
+    }
+
+    assets::invalid_events::store_invalid_events(pool, &mt_events.invalid).await
+}
+
+async fn detect_mt_db_error(async_error: &AsyncError<diesel::result::Error>) -> bool {
+    assets::events::detect_db_error(
+        async_error,
+        "assets__multi_token_events_pkey",
+        "assets__multi_token_events_unique",
+    )
+    .await
+}
+
+/// The fields of the NEP-245 event entry we store, they are the same for all the event kinds
+struct MtEventEntry<'a> {
+    event_kind: models::enums::MtEventKind,
+    old_owner_id: Option<&'a str>,
+    new_owner_id: Option<&'a str>,
+    authorized_id: Option<&'a str>,
+    token_ids: &'a [String],
+    amounts: &'a [String],
+    memo: Option<&'a str>,
+}
+
+pub(crate) fn compose_mt_db_events(
+    events_with_outcomes: &[(
+        assets::event_types::Nep245Event,
+        &near_indexer_primitives::IndexerExecutionOutcomeWithReceipt,
+    )],
+    block_timestamp: u64,
+    shard_id: &near_indexer_primitives::types::ShardId,
+    strict_events_validation: bool,
+) -> ShardEventLines<models::assets::multi_token_events::MultiTokenEvent> {
+    let mut mt_events = ShardEventLines::new(strict_events_validation, block_timestamp, *shard_id);
+    for (event, outcome) in events_with_outcomes {
+        match &event.event_kind {
+            event_types::Nep245EventKind::MtMint(mint_events) => {
+                for mint_event in mint_events {
+                    if mt_events.accept(mint_event, outcome) {
+                        let entry = MtEventEntry {
+                            event_kind: models::enums::MtEventKind::Mint,
+                            old_owner_id: None,
+                            new_owner_id: Some(&mint_event.owner_id),
+                            authorized_id: None,
+                            token_ids: &mint_event.token_ids,
+                            amounts: &mint_event.amounts,
+                            memo: mint_event.memo.as_deref(),
+                        };
+                        push_mt_db_events(&mut mt_events, entry, outcome);
+                    }
+                }
+            }
+            event_types::Nep245EventKind::MtTransfer(transfer_events) => {
+                for transfer_event in transfer_events {
+                    if mt_events.accept(transfer_event, outcome) {
+                        let entry = MtEventEntry {
+                            event_kind: models::enums::MtEventKind::Transfer,
+                            old_owner_id: Some(&transfer_event.old_owner_id),
+                            new_owner_id: Some(&transfer_event.new_owner_id),
+                            authorized_id: transfer_event.authorized_id.as_deref(),
+                            token_ids: &transfer_event.token_ids,
+                            amounts: &transfer_event.amounts,
+                            memo: transfer_event.memo.as_deref(),
+                        };
+                        push_mt_db_events(&mut mt_events, entry, outcome);
+                    }
+                }
+            }
+            event_types::Nep245EventKind::MtBurn(burn_events) => {
+                for burn_event in burn_events {
+                    if mt_events.accept(burn_event, outcome) {
+                        let entry = MtEventEntry {
+                            event_kind: models::enums::MtEventKind::Burn,
+                            old_owner_id: Some(&burn_event.owner_id),
+                            new_owner_id: None,
+                            authorized_id: burn_event.authorized_id.as_deref(),
+                            token_ids: &burn_event.token_ids,
+                            amounts: &burn_event.amounts,
+                            memo: burn_event.memo.as_deref(),
+                        };
+                        push_mt_db_events(&mut mt_events, entry, outcome);
+                    }
+                }
+            }
+        }
+    }
+    mt_events
+}
+
+/// NEP-245 events carry `token_ids` and `amounts` as two parallel arrays,
+/// we pair them up so each DB line contains exactly one token with its amount.
+/// The entry is rejected as a whole if the arrays have different lengths,
+/// we don't want to store a part of the event.
+/// Such entries get to `assets__invalid_events` only with strict events validation
+fn push_mt_db_events(
+    mt_events: &mut ShardEventLines<models::assets::multi_token_events::MultiTokenEvent>,
+    entry: MtEventEntry,
+    outcome: &near_indexer_primitives::IndexerExecutionOutcomeWithReceipt,
+) {
+    if entry.token_ids.len() != entry.amounts.len() {
+        warn!(
+            target: crate::EXPLORER_DATABASE,
+            "assets::multi_token_events: `token_ids` and `amounts` have different lengths ({} and {}) in receipt {}. The event entry is ignored",
+            entry.token_ids.len(),
+            entry.amounts.len(),
+            outcome.receipt.receipt_id,
+        );
+        return;
+    }
+    for (token_id, amount) in entry.token_ids.iter().zip(entry.amounts) {
+        let index_in_shard = mt_events.next_index();
+        mt_events
+            .valid
+            .push(models::assets::multi_token_events::MultiTokenEvent {
+                emitted_for_receipt_id: outcome.receipt.receipt_id.to_string(),
+                emitted_at_block_timestamp: mt_events.block_timestamp(),
+                emitted_in_shard_id: mt_events.shard_id(),
+                emitted_index_of_event_entry_in_shard: index_in_shard,
+                emitted_by_contract_account_id: outcome.receipt.receiver_id.to_string(),
+                token_id: token_id.escape_default().to_string(),
+                amount: amount.escape_default().to_string(),
+                event_kind: entry.event_kind.clone(),
+                token_old_owner_account_id: entry
+                    .old_owner_id
+                    .unwrap_or_default()
+                    .escape_default()
+                    .to_string(),
+                token_new_owner_account_id: entry
+                    .new_owner_id
+                    .unwrap_or_default()
+                    .escape_default()
+                    .to_string(),
+                token_authorized_account_id: entry
+                    .authorized_id
+                    .unwrap_or_default()
+                    .escape_default()
+                    .to_string(),
+                event_memo: entry.memo.unwrap_or_default().escape_default().to_string(),
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compose_from_log(
+        log: &str,
+        strict_events_validation: bool,
+    ) -> ShardEventLines<models::assets::multi_token_events::MultiTokenEvent> {
+        let outcome = crate::adapters::test_utils::function_call_outcome(
+            "alice.near",
+            "mt.near",
+            vec![],
+            vec![log],
+            near_indexer_primitives::views::ExecutionStatusView::SuccessValue(vec![]),
+        );
+        let event = match serde_json::from_str(log).unwrap() {
+            event_types::NearEvent::Nep245(event) => event,
+            other => panic!("NEP-245 event expected, got {:?}", other),
+        };
+        compose_mt_db_events(&[(event, &outcome)], 1, &0, strict_events_validation)
+    }
+
+    #[test]
+    fn test_each_token_gets_its_own_line() {
+        let mt_events = compose_from_log(
+            r#"{"standard":"nep245","version":"1.0.0","event":"mt_transfer","data":[
+                {"old_owner_id":"alice.near","new_owner_id":"bob.near","token_ids":["1","2"],"amounts":["10","20"],"memo":"gift"}
+            ]}"#,
+            false,
+        )
+        .valid;
+
+        assert_eq!(mt_events.len(), 2);
+        for (index, (mt_event, (token_id, amount))) in
+            mt_events.iter().zip([("1", "10"), ("2", "20")]).enumerate()
+        {
+            assert_eq!(mt_event.emitted_index_of_event_entry_in_shard, index as i32);
+            assert_eq!(mt_event.emitted_by_contract_account_id, "mt.near");
+            assert_eq!(mt_event.token_id, token_id);
+            assert_eq!(mt_event.amount, amount);
+            assert!(matches!(
+                mt_event.event_kind,
+                models::enums::MtEventKind::Transfer
+            ));
+            assert_eq!(mt_event.token_old_owner_account_id, "alice.near");
+            assert_eq!(mt_event.token_new_owner_account_id, "bob.near");
+            assert_eq!(mt_event.token_authorized_account_id, "");
+            assert_eq!(mt_event.event_memo, "gift");
+        }
+    }
+
+    #[test]
+    fn test_mint_and_burn_fill_one_owner() {
+        let minted = compose_from_log(
+            r#"{"standard":"nep245","version":"1.0.0","event":"mt_mint","data":[
+                {"owner_id":"alice.near","token_ids":["1"],"amounts":["10"]}
+            ]}"#,
+            false,
+        )
+        .valid;
+        assert!(matches!(
+            minted[0].event_kind,
+            models::enums::MtEventKind::Mint
+        ));
+        assert_eq!(minted[0].token_old_owner_account_id, "");
+        assert_eq!(minted[0].token_new_owner_account_id, "alice.near");
+
+        let burnt = compose_from_log(
+            r#"{"standard":"nep245","version":"1.0.0","event":"mt_burn","data":[
+                {"owner_id":"alice.near","authorized_id":"bob.near","token_ids":["1"],"amounts":["10"]}
+            ]}"#,
+            false,
+        )
+        .valid;
+        assert!(matches!(
+            burnt[0].event_kind,
+            models::enums::MtEventKind::Burn
+        ));
+        assert_eq!(burnt[0].token_old_owner_account_id, "alice.near");
+        assert_eq!(burnt[0].token_new_owner_account_id, "");
+        assert_eq!(burnt[0].token_authorized_account_id, "bob.near");
+    }
+
+    #[test]
+    fn test_entry_with_mismatched_amounts_is_rejected() {
+        let mt_events = compose_from_log(
+            r#"{"standard":"nep245","version":"1.0.0","event":"mt_mint","data":[
+                {"owner_id":"alice.near","token_ids":["1","2"],"amounts":["10"]},
+                {"owner_id":"bob.near","token_ids":["3"],"amounts":["30"]}
+            ]}"#,
+            false,
+        )
+        .valid;
+
+        assert_eq!(mt_events.len(), 1);
+        assert_eq!(mt_events[0].token_id, "3");
+        assert_eq!(mt_events[0].emitted_index_of_event_entry_in_shard, 0);
+    }
+
+    #[test]
+    fn test_entry_with_mismatched_amounts_keeps_its_index_with_strict_validation() {
+        let mt_events = compose_from_log(
+            r#"{"standard":"nep245","version":"1.0.0","event":"mt_mint","data":[
+                {"owner_id":"alice.near","token_ids":["1","2"],"amounts":["10"]},
+                {"owner_id":"bob.near","token_ids":["3"],"amounts":["30"]}
+            ]}"#,
+            true,
+        );
+
+        assert_eq!(mt_events.invalid.len(), 1);
+        assert_eq!(
+            mt_events.invalid[0].emitted_index_of_event_entry_in_shard,
+            0
+        );
+        assert!(matches!(
+            mt_events.invalid[0].invalidity_reason,
+            models::enums::EventInvalidityReason::TokenIdsAndAmountsMismatch
+        ));
+        assert_eq!(mt_events.valid.len(), 1);
+        assert_eq!(mt_events.valid[0].emitted_index_of_event_entry_in_shard, 1);
+    }
+}
@@ -1,6 +1,5 @@
 use actix_diesel::dsl::AsyncRunQueryDsl;
 use actix_diesel::{AsyncError, Database};
-use bigdecimal::BigDecimal;
 use diesel::PgConnection;
 
 use crate::adapters::{assets, CHUNK_SIZE_FOR_BATCH_INSERT};
@@ -8,19 +7,13 @@ use crate::models;
 use crate::schema;
 
 use super::event_types;
+use super::validation::ShardEventLines;
 
 pub(crate) async fn store_nft_events(
     pool: &Database<PgConnection>,
-    shard: &near_indexer_primitives::IndexerShard,
-    block_timestamp: u64,
-    events_with_outcomes: &[(
-        assets::event_types::Nep171Event,
-        &near_indexer_primitives::IndexerExecutionOutcomeWithReceipt,
-    )],
+    nft_events: &ShardEventLines<models::assets::non_fungible_token_events::NonFungibleTokenEvent>,
 ) -> anyhow::Result<()> {
-    let nft_events = compose_nft_db_events(events_with_outcomes, block_timestamp, &shard.shard_id);
-
-    for chunk in nft_events.chunks(CHUNK_SIZE_FOR_BATCH_INSERT) {
+    for chunk in nft_events.valid.chunks(CHUNK_SIZE_FOR_BATCH_INSERT) {
         let nft_events_chunk = chunk.to_owned();
         crate::await_retry_or_panic!(
             diesel::insert_into(schema::assets__non_fungible_token_events::table)
@@ -33,7 +26,7 @@ pub(crate) async fn store_nft_events(
         );
     }
 
-    Ok(())
+    assets::invalid_events::store_invalid_events(pool, &nft_events.invalid).await
 }
 
 async fn detect_nft_db_error(async_error: &AsyncError<diesel::result::Error>) -> bool {
@@ -45,28 +38,33 @@ async fn detect_nft_db_error(async_error: &AsyncError<diesel::result::Error>) ->
     .await
 }
 
-fn compose_nft_db_events(
+pub(crate) fn compose_nft_db_events(
     events_with_outcomes: &[(
         assets::event_types::Nep171Event,
         &near_indexer_primitives::IndexerExecutionOutcomeWithReceipt,
     )],
     block_timestamp: u64,
     shard_id: &near_indexer_primitives::types::ShardId,
-) -> Vec<models::assets::non_fungible_token_events::NonFungibleTokenEvent> {
-    let mut nft_events = Vec::new();
+    strict_events_validation: bool,
+) -> ShardEventLines<models::assets::non_fungible_token_events::NonFungibleTokenEvent> {
+    let mut nft_events = ShardEventLines::new(strict_events_validation, block_timestamp, *shard_id);
     for (event, outcome) in events_with_outcomes {
         let contract_id = &outcome.receipt.receiver_id;
         match &event.event_kind {
             event_types::Nep171EventKind::NftMint(mint_events) => {
                 for mint_event in mint_events {
+                    if !nft_events.accept(mint_event, outcome) {
+                        continue;
+                    }
                     let memo = mint_event.memo.clone().unwrap_or_default();
                     for token_id in &mint_event.token_ids {
-                        nft_events.push(
+                        let index_in_shard = nft_events.next_index();
+                        nft_events.valid.push(
                             models::assets::non_fungible_token_events::NonFungibleTokenEvent {
                                 emitted_for_receipt_id: outcome.receipt.receipt_id.to_string(),
-                                emitted_at_block_timestamp: BigDecimal::from(block_timestamp),
-                                emitted_in_shard_id: BigDecimal::from(*shard_id),
-                                emitted_index_of_event_entry_in_shard: nft_events.len() as i32,
+                                emitted_at_block_timestamp: nft_events.block_timestamp(),
+                                emitted_in_shard_id: nft_events.shard_id(),
+                                emitted_index_of_event_entry_in_shard: index_in_shard,
                                 emitted_by_contract_account_id: contract_id.to_string(),
                                 token_id: token_id.escape_default().to_string(),
                                 event_kind: models::enums::NftEventKind::Mint,
@@ -84,15 +82,19 @@ fn compose_nft_db_events(
             }
             event_types::Nep171EventKind::NftTransfer(transfer_events) => {
                 for transfer_event in transfer_events {
+                    if !nft_events.accept(transfer_event, outcome) {
+                        continue;
+                    }
                     let authorized_id = transfer_event.authorized_id.clone().unwrap_or_default();
                     let memo = transfer_event.memo.clone().unwrap_or_default();
                     for token_id in &transfer_event.token_ids {
-                        nft_events.push(
+                        let index_in_shard = nft_events.next_index();
+                        nft_events.valid.push(
                             models::assets::non_fungible_token_events::NonFungibleTokenEvent {
                                 emitted_for_receipt_id: outcome.receipt.receipt_id.to_string(),
-                                emitted_at_block_timestamp: BigDecimal::from(block_timestamp),
-                                emitted_in_shard_id: BigDecimal::from(*shard_id),
-                                emitted_index_of_event_entry_in_shard: nft_events.len() as i32,
+                                emitted_at_block_timestamp: nft_events.block_timestamp(),
+                                emitted_in_shard_id: nft_events.shard_id(),
+                                emitted_index_of_event_entry_in_shard: index_in_shard,
                                 emitted_by_contract_account_id: contract_id.to_string(),
                                 token_id: token_id.escape_default().to_string(),
                                 event_kind: models::enums::NftEventKind::Transfer,
@@ -115,15 +117,19 @@ fn compose_nft_db_events(
             }
             event_types::Nep171EventKind::NftBurn(burn_events) => {
                 for burn_event in burn_events {
+                    if !nft_events.accept(burn_event, outcome) {
+                        continue;
+                    }
                     let authorized_id = &burn_event.authorized_id.clone().unwrap_or_default();
                     let memo = burn_event.memo.clone().unwrap_or_default();
                     for token_id in &burn_event.token_ids {
-                        nft_events.push(
+                        let index_in_shard = nft_events.next_index();
+                        nft_events.valid.push(
                             models::assets::non_fungible_token_events::NonFungibleTokenEvent {
                                 emitted_for_receipt_id: outcome.receipt.receipt_id.to_string(),
-                                emitted_at_block_timestamp: BigDecimal::from(block_timestamp),
-                                emitted_in_shard_id: BigDecimal::from(*shard_id),
-                                emitted_index_of_event_entry_in_shard: nft_events.len() as i32,
+                                emitted_at_block_timestamp: nft_events.block_timestamp(),
+                                emitted_in_shard_id: nft_events.shard_id(),
+                                emitted_index_of_event_entry_in_shard: index_in_shard,
                                 emitted_by_contract_account_id: contract_id.to_string(),
                                 token_id: token_id.escape_default().to_string(),
                                 event_kind: models::enums::NftEventKind::Burn,
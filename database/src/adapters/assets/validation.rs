@@ -0,0 +1,402 @@
+use bigdecimal::BigDecimal;
+use serde::Serialize;
+
+use crate::models;
+use crate::models::enums::EventInvalidityReason;
+
+use super::event_types;
+
+/// Event entry which is checked before it is stored to the assets tables
+pub(crate) trait EventEntry: Serialize {
+    const STANDARD: &'static str;
+    const EVENT_KIND: &'static str;
+
+    /// Returns the reason why the entry is considered malformed.
+    /// We validate the raw data provided by the contract, before any escaping happens
+    fn validate(&self, predecessor_id: &str, contract_id: &str) -> Option<EventInvalidityReason>;
+}
+
+/// The lines of one standard composed for the shard.
+/// The invalid entries take their index from the same counter as the valid lines,
+/// so they keep their place among the events of the shard
+pub(crate) struct ShardEventLines<T> {
+    pub valid: Vec<T>,
+    pub invalid: Vec<models::assets::invalid_events::InvalidEvent>,
+    strict_events_validation: bool,
+    block_timestamp: u64,
+    shard_id: near_indexer_primitives::types::ShardId,
+}
+
+impl<T> ShardEventLines<T> {
+    pub fn new(
+        strict_events_validation: bool,
+        block_timestamp: u64,
+        shard_id: near_indexer_primitives::types::ShardId,
+    ) -> Self {
+        Self {
+            valid: Vec::new(),
+            invalid: Vec::new(),
+            strict_events_validation,
+            block_timestamp,
+            shard_id,
+        }
+    }
+
+    pub fn block_timestamp(&self) -> BigDecimal {
+        BigDecimal::from(self.block_timestamp)
+    }
+
+    pub fn shard_id(&self) -> BigDecimal {
+        BigDecimal::from(self.shard_id)
+    }
+
+    pub fn next_index(&self) -> i32 {
+        (self.valid.len() + self.invalid.len()) as i32
+    }
+
+    /// Returns `false` if the entry did not pass the validation, it is added to the invalid lines then.
+    /// Everything is accepted without strict events validation
+    pub fn accept<E: EventEntry>(
+        &mut self,
+        entry: &E,
+        outcome: &near_indexer_primitives::IndexerExecutionOutcomeWithReceipt,
+    ) -> bool {
+        if !self.strict_events_validation {
+            return true;
+        }
+        let reason = match entry.validate(
+            outcome.receipt.predecessor_id.as_str(),
+            outcome.receipt.receiver_id.as_str(),
+        ) {
+            Some(reason) => reason,
+            None => return true,
+        };
+
+        let mut data = serde_json::to_value(entry)
+            .expect("Event entries consist of strings, so they are serializable");
+        crate::models::escape_json(&mut data);
+        self.invalid
+            .push(models::assets::invalid_events::InvalidEvent {
+                emitted_for_receipt_id: outcome.receipt.receipt_id.to_string(),
+                emitted_at_block_timestamp: self.block_timestamp(),
+                emitted_in_shard_id: self.shard_id(),
+                emitted_index_of_event_entry_in_shard: self.next_index(),
+                emitted_by_contract_account_id: outcome.receipt.receiver_id.to_string(),
+                event_standard: E::STANDARD.to_string(),
+                event_kind: E::EVENT_KIND.to_string(),
+                event_data: data,
+                invalidity_reason: reason,
+            });
+        false
+    }
+}
+
+impl EventEntry for event_types::FtMintData {
+    const STANDARD: &'static str = "nep141";
+    const EVENT_KIND: &'static str = "ft_mint";
+
+    fn validate(&self, _: &str, _: &str) -> Option<EventInvalidityReason> {
+        validate_amount(&self.amount).or_else(|| validate_account_id(&self.owner_id))
+    }
+}
+
+impl EventEntry for event_types::FtTransferData {
+    const STANDARD: &'static str = "nep141";
+    const EVENT_KIND: &'static str = "ft_transfer";
+
+    fn validate(&self, predecessor_id: &str, contract_id: &str) -> Option<EventInvalidityReason> {
+        validate_amount(&self.amount)
+            .or_else(|| validate_account_id(&self.old_owner_id))
+            .or_else(|| validate_account_id(&self.new_owner_id))
+            .or_else(|| {
+                validate_transfer_initiator(&self.old_owner_id, None, predecessor_id, contract_id)
+            })
+    }
+}
+
+impl EventEntry for event_types::FtBurnData {
+    const STANDARD: &'static str = "nep141";
+    const EVENT_KIND: &'static str = "ft_burn";
+
+    fn validate(&self, _: &str, _: &str) -> Option<EventInvalidityReason> {
+        validate_amount(&self.amount).or_else(|| validate_account_id(&self.owner_id))
+    }
+}
+
+/// The transfers restored from the function calls are checked the same way as `ft_transfer` events
+#[cfg(feature = "fungible_token_events")]
+impl EventEntry for super::legacy_ft_transfers::LegacyFtTransfer {
+    const STANDARD: &'static str = "nep141";
+    const EVENT_KIND: &'static str = "ft_transfer";
+
+    fn validate(&self, predecessor_id: &str, contract_id: &str) -> Option<EventInvalidityReason> {
+        validate_amount(&self.amount)
+            .or_else(|| validate_account_id(&self.old_owner_id))
+            .or_else(|| validate_account_id(&self.new_owner_id))
+            .or_else(|| {
+                validate_transfer_initiator(&self.old_owner_id, None, predecessor_id, contract_id)
+            })
+    }
+}
+
+impl EventEntry for event_types::NftMintData {
+    const STANDARD: &'static str = "nep171";
+    const EVENT_KIND: &'static str = "nft_mint";
+
+    fn validate(&self, _: &str, _: &str) -> Option<EventInvalidityReason> {
+        validate_account_id(&self.owner_id)
+    }
+}
+
+impl EventEntry for event_types::NftTransferData {
+    const STANDARD: &'static str = "nep171";
+    const EVENT_KIND: &'static str = "nft_transfer";
+
+    fn validate(&self, predecessor_id: &str, contract_id: &str) -> Option<EventInvalidityReason> {
+        validate_account_id(&self.old_owner_id)
+            .or_else(|| validate_account_id(&self.new_owner_id))
+            .or_else(|| validate_optional_account_id(&self.authorized_id))
+            .or_else(|| {
+                validate_transfer_initiator(
+                    &self.old_owner_id,
+                    self.authorized_id.as_deref(),
+                    predecessor_id,
+                    contract_id,
+                )
+            })
+    }
+}
+
+impl EventEntry for event_types::NftBurnData {
+    const STANDARD: &'static str = "nep171";
+    const EVENT_KIND: &'static str = "nft_burn";
+
+    fn validate(&self, _: &str, _: &str) -> Option<EventInvalidityReason> {
+        validate_account_id(&self.owner_id)
+            .or_else(|| validate_optional_account_id(&self.authorized_id))
+    }
+}
+
+impl EventEntry for event_types::MtMintData {
+    const STANDARD: &'static str = "nep245";
+    const EVENT_KIND: &'static str = "mt_mint";
+
+    fn validate(&self, _: &str, _: &str) -> Option<EventInvalidityReason> {
+        validate_amounts(&self.token_ids, &self.amounts)
+            .or_else(|| validate_account_id(&self.owner_id))
+    }
+}
+
+impl EventEntry for event_types::MtTransferData {
+    const STANDARD: &'static str = "nep245";
+    const EVENT_KIND: &'static str = "mt_transfer";
+
+    fn validate(&self, predecessor_id: &str, contract_id: &str) -> Option<EventInvalidityReason> {
+        validate_amounts(&self.token_ids, &self.amounts)
+            .or_else(|| validate_account_id(&self.old_owner_id))
+            .or_else(|| validate_account_id(&self.new_owner_id))
+            .or_else(|| validate_optional_account_id(&self.authorized_id))
+            .or_else(|| {
+                validate_transfer_initiator(
+                    &self.old_owner_id,
+                    self.authorized_id.as_deref(),
+                    predecessor_id,
+                    contract_id,
+                )
+            })
+    }
+}
+
+impl EventEntry for event_types::MtBurnData {
+    const STANDARD: &'static str = "nep245";
+    const EVENT_KIND: &'static str = "mt_burn";
+
+    fn validate(&self, _: &str, _: &str) -> Option<EventInvalidityReason> {
+        validate_amounts(&self.token_ids, &self.amounts)
+            .or_else(|| validate_account_id(&self.owner_id))
+            .or_else(|| validate_optional_account_id(&self.authorized_id))
+    }
+}
+
+fn validate_amount(amount: &str) -> Option<EventInvalidityReason> {
+    if amount.parse::<u128>().is_ok() {
+        None
+    } else {
+        Some(EventInvalidityReason::InvalidAmount)
+    }
+}
+
+fn validate_amounts(token_ids: &[String], amounts: &[String]) -> Option<EventInvalidityReason> {
+    if token_ids.len() != amounts.len() {
+        return Some(EventInvalidityReason::TokenIdsAndAmountsMismatch);
+    }
+    amounts.iter().find_map(|amount| validate_amount(amount))
+}
+
+fn validate_account_id(account_id: &str) -> Option<EventInvalidityReason> {
+    if account_id
+        .parse::<near_indexer_primitives::types::AccountId>()
+        .is_ok()
+    {
+        None
+    } else {
+        Some(EventInvalidityReason::InvalidAccountId)
+    }
+}
+
+fn validate_optional_account_id(account_id: &Option<String>) -> Option<EventInvalidityReason> {
+    account_id.as_deref().and_then(validate_account_id)
+}
+
+/// The tokens can be moved by the owner, by the account the owner authorized to do that,
+/// or by the contract itself (e.g. refunds in `ft_resolve_transfer`)
+fn validate_transfer_initiator(
+    old_owner_id: &str,
+    authorized_id: Option<&str>,
+    predecessor_id: &str,
+    contract_id: &str,
+) -> Option<EventInvalidityReason> {
+    if predecessor_id == old_owner_id
+        || predecessor_id == contract_id
+        || authorized_id == Some(predecessor_id)
+    {
+        None
+    } else {
+        Some(EventInvalidityReason::TransferNotByOwner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ft_transfer(
+        old_owner_id: &str,
+        new_owner_id: &str,
+        amount: &str,
+    ) -> event_types::FtTransferData {
+        event_types::FtTransferData {
+            old_owner_id: old_owner_id.to_string(),
+            new_owner_id: new_owner_id.to_string(),
+            amount: amount.to_string(),
+            memo: None,
+        }
+    }
+
+    #[test]
+    fn test_amounts_should_be_u128() {
+        assert!(validate_amount("340282366920938463463374607431768211455").is_none());
+        for amount in [
+            "340282366920938463463374607431768211456",
+            "-1",
+            "1.5",
+            "",
+            "0x10",
+        ] {
+            assert!(matches!(
+                validate_amount(amount),
+                Some(EventInvalidityReason::InvalidAmount)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_token_ids_and_amounts_should_match() {
+        let token_ids = vec!["1".to_string(), "2".to_string()];
+        assert!(validate_amounts(&token_ids, &["1".to_string(), "2".to_string()]).is_none());
+        assert!(matches!(
+            validate_amounts(&token_ids, &["1".to_string()]),
+            Some(EventInvalidityReason::TokenIdsAndAmountsMismatch)
+        ));
+        assert!(matches!(
+            validate_amounts(&token_ids, &["1".to_string(), "two".to_string()]),
+            Some(EventInvalidityReason::InvalidAmount)
+        ));
+    }
+
+    #[test]
+    fn test_account_ids_should_be_valid() {
+        assert!(validate_account_id("alice.near").is_none());
+        for account_id in ["", "Alice.near", "alice..near", "a"] {
+            assert!(matches!(
+                validate_account_id(account_id),
+                Some(EventInvalidityReason::InvalidAccountId)
+            ));
+        }
+        assert!(validate_optional_account_id(&None).is_none());
+    }
+
+    #[test]
+    fn test_transfer_initiator() {
+        let transfer = ft_transfer("alice.near", "bob.near", "10");
+        // By the owner
+        assert!(transfer.validate("alice.near", "ft.near").is_none());
+        // By the contract itself
+        assert!(transfer.validate("ft.near", "ft.near").is_none());
+        assert!(matches!(
+            transfer.validate("bob.near", "ft.near"),
+            Some(EventInvalidityReason::TransferNotByOwner)
+        ));
+
+        let nft_transfer = event_types::NftTransferData {
+            authorized_id: Some("market.near".to_string()),
+            old_owner_id: "alice.near".to_string(),
+            new_owner_id: "bob.near".to_string(),
+            token_ids: vec!["1".to_string()],
+            memo: None,
+        };
+        assert!(nft_transfer.validate("market.near", "nft.near").is_none());
+        assert!(matches!(
+            nft_transfer.validate("bob.near", "nft.near"),
+            Some(EventInvalidityReason::TransferNotByOwner)
+        ));
+    }
+
+    #[test]
+    fn test_invalid_entries_share_the_numbering_with_valid_ones() {
+        let outcome = crate::adapters::test_utils::function_call_outcome(
+            "alice.near",
+            "ft.near",
+            vec![],
+            vec![],
+            near_indexer_primitives::views::ExecutionStatusView::SuccessValue(vec![]),
+        );
+        let mut lines = ShardEventLines::<i32>::new(true, 1, 0);
+        for transfer in [
+            ft_transfer("alice.near", "bob.near", "10"),
+            ft_transfer("alice.near", "bob.near", "ten"),
+            ft_transfer("alice.near", "bob.near", "20"),
+        ] {
+            if lines.accept(&transfer, &outcome) {
+                let index = lines.next_index();
+                lines.valid.push(index);
+            }
+        }
+
+        assert_eq!(lines.valid, vec![0, 2]);
+        assert_eq!(lines.invalid.len(), 1);
+        let invalid_event = &lines.invalid[0];
+        assert_eq!(invalid_event.emitted_index_of_event_entry_in_shard, 1);
+        assert_eq!(invalid_event.event_standard, "nep141");
+        assert_eq!(invalid_event.event_kind, "ft_transfer");
+        assert_eq!(invalid_event.event_data["amount"], "ten");
+        assert!(matches!(
+            invalid_event.invalidity_reason,
+            EventInvalidityReason::InvalidAmount
+        ));
+    }
+
+    #[test]
+    fn test_everything_is_accepted_without_strict_validation() {
+        let outcome = crate::adapters::test_utils::function_call_outcome(
+            "bob.near",
+            "ft.near",
+            vec![],
+            vec![],
+            near_indexer_primitives::views::ExecutionStatusView::SuccessValue(vec![]),
+        );
+        let mut lines = ShardEventLines::<()>::new(false, 1, 0);
+        assert!(lines.accept(&ft_transfer("alice.near", "bob.near", "ten"), &outcome));
+        assert!(lines.invalid.is_empty());
+    }
+}
@@ -0,0 +1,27 @@
+use actix_diesel::dsl::AsyncRunQueryDsl;
+use diesel::PgConnection;
+
+use crate::models;
+use crate::schema;
+
+/// Saves the burnt fees of the block, they are summed up from the chunks
+/// and the receipt execution outcomes of the streamer message
+pub async fn store_block_fees(
+    pool: &actix_diesel::Database<PgConnection>,
+    block: &near_indexer_primitives::views::BlockView,
+    shards: &[near_indexer_primitives::IndexerShard],
+) -> anyhow::Result<()> {
+    let block_fees_model = models::block_fees::BlockFees::new(block, shards);
+
+    crate::await_retry_or_panic!(
+        diesel::insert_into(schema::block_fees::table)
+            .values(block_fees_model.clone())
+            .on_conflict_do_nothing()
+            .execute_async(pool),
+        10,
+        "BlockFees were stored to database".to_string(),
+        &block_fees_model
+    );
+
+    Ok(())
+}
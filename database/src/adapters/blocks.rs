@@ -22,6 +22,18 @@ pub async fn store_block(
         "Block was stored to database".to_string(),
         &block_model
     );
+
+    let block_header_model = models::blocks::BlockHeader::from(block);
+
+    crate::await_retry_or_panic!(
+        diesel::insert_into(schema::block_headers::table)
+            .values(block_header_model.clone())
+            .on_conflict_do_nothing()
+            .execute_async(pool),
+        10,
+        "BlockHeader was stored to database".to_string(),
+        &block_header_model
+    );
     Ok(())
 }
 
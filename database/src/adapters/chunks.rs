@@ -4,7 +4,7 @@ use diesel::PgConnection;
 use crate::models;
 use crate::schema;
 
-/// Saves chunks to database
+/// Saves chunks to database, the shards without the chunk are saved as missing chunks
 pub async fn store_chunks(
     pool: &actix_diesel::Database<PgConnection>,
     shards: &[near_indexer_primitives::IndexerShard],
@@ -18,19 +18,37 @@ pub async fn store_chunks(
         .filter_map(|shard| shard.chunk.as_ref())
         .map(|chunk| models::chunks::Chunk::from_chunk_view(chunk, block_hash))
         .collect();
+    let missing_chunk_models: Vec<models::chunks::MissingChunk> = shards
+        .iter()
+        .filter(|shard| shard.chunk.is_none())
+        .map(|shard| models::chunks::MissingChunk {
+            included_in_block_hash: block_hash.to_string(),
+            shard_id: shard.shard_id.into(),
+        })
+        .collect();
 
-    if chunk_models.is_empty() {
-        return Ok(());
+    if !chunk_models.is_empty() {
+        crate::await_retry_or_panic!(
+            diesel::insert_into(schema::chunks::table)
+                .values(chunk_models.clone())
+                .on_conflict_do_nothing()
+                .execute_async(pool),
+            10,
+            "Chunks were stored to database".to_string(),
+            &chunk_models
+        );
     }
 
-    crate::await_retry_or_panic!(
-        diesel::insert_into(schema::chunks::table)
-            .values(chunk_models.clone())
-            .on_conflict_do_nothing()
-            .execute_async(pool),
-        10,
-        "Chunks were stored to database".to_string(),
-        &chunk_models
-    );
+    if !missing_chunk_models.is_empty() {
+        crate::await_retry_or_panic!(
+            diesel::insert_into(schema::missing_chunks::table)
+                .values(missing_chunk_models.clone())
+                .on_conflict_do_nothing()
+                .execute_async(pool),
+            10,
+            "MissingChunks were stored to database".to_string(),
+            &missing_chunk_models
+        );
+    }
     Ok(())
 }
@@ -0,0 +1,103 @@
+use actix_diesel::dsl::AsyncRunQueryDsl;
+use diesel::PgConnection;
+
+use near_indexer_primitives::views::{ActionView, ExecutionStatusView, ReceiptEnumView};
+
+use crate::adapters::CHUNK_SIZE_FOR_BATCH_INSERT;
+use crate::models;
+use crate::schema;
+
+/// Saves successful contract deployments with the sha256 of the code.
+/// With `store_code` the codes themselves are stored as well, each code once
+pub async fn store_contract_deployments(
+    pool: &actix_diesel::Database<PgConnection>,
+    shards: &[near_indexer_primitives::IndexerShard],
+    block_height: near_indexer_primitives::types::BlockHeight,
+    block_timestamp: u64,
+    store_code: bool,
+) -> anyhow::Result<()> {
+    let deployed_codes: Vec<(&near_indexer_primitives::views::ReceiptView, usize, &[u8])> = shards
+        .iter()
+        .flat_map(|shard| shard.receipt_execution_outcomes.iter())
+        .filter(|outcome| {
+            matches!(
+                outcome.execution_outcome.outcome.status,
+                ExecutionStatusView::SuccessValue(_) | ExecutionStatusView::SuccessReceiptId(_)
+            )
+        })
+        .flat_map(|outcome| {
+            let actions: &[ActionView] = match &outcome.receipt.receipt {
+                ReceiptEnumView::Action { actions, .. } => actions,
+                ReceiptEnumView::Data { .. } => &[],
+            };
+            actions
+                .iter()
+                .enumerate()
+                .filter_map(move |(index, action)| match action {
+                    ActionView::DeployContract { code } => {
+                        Some((&outcome.receipt, index, code.as_slice()))
+                    }
+                    _ => None,
+                })
+        })
+        .collect();
+
+    if deployed_codes.is_empty() {
+        return Ok(());
+    }
+
+    if store_code {
+        let mut contract_code_models: Vec<models::contract_deployments::ContractCode> = Vec::new();
+        for (_, _, code) in &deployed_codes {
+            let contract_code = models::contract_deployments::ContractCode::from_code(code);
+            if contract_code_models
+                .iter()
+                .all(|model| model.code_hash != contract_code.code_hash)
+            {
+                contract_code_models.push(contract_code);
+            }
+        }
+
+        // The codes are heavy, so we store them one by one
+        for contract_code in contract_code_models {
+            crate::await_retry_or_panic!(
+                diesel::insert_into(schema::contract_codes::table)
+                    .values(contract_code.clone())
+                    .on_conflict_do_nothing()
+                    .execute_async(pool),
+                10,
+                "ContractCode was stored to database".to_string(),
+                &contract_code
+            );
+        }
+    }
+
+    let contract_deployment_models: Vec<models::contract_deployments::ContractDeployment> =
+        deployed_codes
+            .into_iter()
+            .map(|(receipt, index, code)| {
+                models::contract_deployments::ContractDeployment::from_deploy_contract_action(
+                    receipt,
+                    index as i32,
+                    code,
+                    block_height,
+                    block_timestamp,
+                )
+            })
+            .collect();
+
+    for chunk in contract_deployment_models.chunks(CHUNK_SIZE_FOR_BATCH_INSERT) {
+        let contract_deployments_chunk = chunk.to_owned();
+        crate::await_retry_or_panic!(
+            diesel::insert_into(schema::contract_deployments::table)
+                .values(contract_deployments_chunk.clone())
+                .on_conflict_do_nothing()
+                .execute_async(pool),
+            10,
+            "ContractDeployments were stored to database".to_string(),
+            &contract_deployments_chunk
+        );
+    }
+
+    Ok(())
+}
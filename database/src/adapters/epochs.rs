@@ -0,0 +1,129 @@
+use std::str::FromStr;
+
+use actix_diesel::dsl::AsyncRunQueryDsl;
+use anyhow::Context;
+use diesel::pg::upsert::excluded;
+use diesel::sql_types::{Integer, Nullable, Numeric, Text};
+use diesel::{ExpressionMethods, PgConnection, QueryDsl};
+
+use crate::models;
+use crate::schema;
+
+sql_function! {
+    #[sql_name = "COALESCE"]
+    fn coalesce_numeric(x: Nullable<Numeric>, y: Nullable<Numeric>) -> Nullable<Numeric>;
+}
+
+sql_function! {
+    #[sql_name = "COALESCE"]
+    fn coalesce_text(x: Nullable<Text>, y: Nullable<Text>) -> Nullable<Text>;
+}
+
+sql_function! {
+    #[sql_name = "COALESCE"]
+    fn coalesce_integer(x: Nullable<Integer>, y: Nullable<Integer>) -> Nullable<Integer>;
+}
+
+/// Saves the epoch started with the given block and closes the previous epoch.
+/// The validators info of the previous epoch (it is finished, so the numbers are final) is stored if provided
+pub async fn store_epoch_start(
+    pool: &actix_diesel::Database<PgConnection>,
+    block_header: &near_indexer_primitives::views::BlockHeaderView,
+    previous_epoch_id: &near_indexer_primitives::CryptoHash,
+    protocol_version: Option<near_indexer_primitives::types::ProtocolVersion>,
+    previous_epoch_validators_info: Option<&near_indexer_primitives::views::EpochValidatorInfo>,
+) -> anyhow::Result<()> {
+    let previous_epoch_model = models::epochs::Epoch::from_next_epoch_start_block_header(
+        previous_epoch_id,
+        block_header,
+        previous_epoch_validators_info,
+    );
+    let epoch_model = models::epochs::Epoch::from_start_block_header(
+        block_header,
+        previous_epoch_validators_info
+            .map(|epoch_validators_info| epoch_validators_info.epoch_height + 1),
+        protocol_version,
+    );
+
+    // The epoch could be stored before without some of the fields (e.g. the epoch which was
+    // in progress when the indexer started, or RPC was not available), we fill in only the gaps
+    crate::await_retry_or_panic!(
+        diesel::insert_into(schema::epochs::table)
+            .values(vec![previous_epoch_model.clone(), epoch_model.clone()])
+            .on_conflict(schema::epochs::dsl::epoch_id)
+            .do_update()
+            .set((
+                schema::epochs::dsl::epoch_height.eq(coalesce_numeric(
+                    schema::epochs::dsl::epoch_height,
+                    excluded(schema::epochs::dsl::epoch_height),
+                )),
+                schema::epochs::dsl::start_block_height.eq(coalesce_numeric(
+                    schema::epochs::dsl::start_block_height,
+                    excluded(schema::epochs::dsl::start_block_height),
+                )),
+                schema::epochs::dsl::start_block_hash.eq(coalesce_text(
+                    schema::epochs::dsl::start_block_hash,
+                    excluded(schema::epochs::dsl::start_block_hash),
+                )),
+                schema::epochs::dsl::start_block_timestamp.eq(coalesce_numeric(
+                    schema::epochs::dsl::start_block_timestamp,
+                    excluded(schema::epochs::dsl::start_block_timestamp),
+                )),
+                schema::epochs::dsl::end_block_height.eq(coalesce_numeric(
+                    schema::epochs::dsl::end_block_height,
+                    excluded(schema::epochs::dsl::end_block_height),
+                )),
+                schema::epochs::dsl::protocol_version.eq(coalesce_integer(
+                    schema::epochs::dsl::protocol_version,
+                    excluded(schema::epochs::dsl::protocol_version),
+                )),
+            ))
+            .execute_async(pool),
+        10,
+        "Epochs were stored to database".to_string(),
+        &epoch_model
+    );
+
+    if let Some(epoch_validators_info) = previous_epoch_validators_info {
+        let epoch_validator_models: Vec<models::epochs::EpochValidator> = epoch_validators_info
+            .current_validators
+            .iter()
+            .map(|validator_info| {
+                models::epochs::EpochValidator::from_validator_info(
+                    previous_epoch_id,
+                    validator_info,
+                )
+            })
+            .collect();
+
+        crate::await_retry_or_panic!(
+            diesel::insert_into(schema::epoch_validators::table)
+                .values(epoch_validator_models.clone())
+                .on_conflict_do_nothing()
+                .execute_async(pool),
+            10,
+            "EpochValidators were stored to database".to_string(),
+            &epoch_validator_models
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns the epoch of the stored block, `None` if the header of the block is not stored
+pub async fn get_block_epoch_id(
+    pool: &actix_diesel::Database<PgConnection>,
+    block_hash: &near_indexer_primitives::CryptoHash,
+) -> anyhow::Result<Option<near_indexer_primitives::CryptoHash>> {
+    schema::block_headers::table
+        .select(schema::block_headers::dsl::epoch_id)
+        .filter(schema::block_headers::dsl::block_hash.eq(block_hash.to_string()))
+        .get_optional_result_async::<String>(pool)
+        .await
+        .context("DB Error")?
+        .map(|epoch_id| {
+            near_indexer_primitives::CryptoHash::from_str(&epoch_id)
+                .map_err(|err| anyhow::anyhow!("Failed to parse epoch_id: {}", err))
+        })
+        .transpose()
+}
@@ -3,14 +3,25 @@ use cached::Cached;
 use diesel::PgConnection;
 use futures::future::try_join_all;
 
+use crate::adapters::CHUNK_SIZE_FOR_BATCH_INSERT;
 use crate::models;
 use crate::schema;
 
+/// Optional data stored along with the execution outcomes
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExecutionOutcomesOptions {
+    /// Store the gas used per cost to `execution_outcome_gas_profiles`
+    pub store_gas_profiles: bool,
+    /// Store the Merkle paths of the outcomes to `execution_outcome_proofs`
+    pub store_proofs: bool,
+}
+
 pub async fn store_execution_outcomes(
     pool: &actix_diesel::Database<PgConnection>,
     shards: &[near_indexer_primitives::IndexerShard],
     block_timestamp: u64,
     receipts_cache_arc: crate::receipts_cache::ReceiptsCacheArc,
+    options: ExecutionOutcomesOptions,
 ) -> anyhow::Result<()> {
     let futures = shards.iter().map(|shard| {
         store_execution_outcomes_for_chunk(
@@ -19,6 +30,7 @@ pub async fn store_execution_outcomes(
             shard.shard_id,
             block_timestamp,
             receipts_cache_arc.clone(),
+            options,
         )
     });
 
@@ -32,10 +44,14 @@ pub async fn store_execution_outcomes_for_chunk(
     shard_id: near_indexer_primitives::types::ShardId,
     block_timestamp: u64,
     receipts_cache_arc: crate::receipts_cache::ReceiptsCacheArc,
+    options: ExecutionOutcomesOptions,
 ) -> anyhow::Result<()> {
     let mut outcome_models: Vec<models::execution_outcomes::ExecutionOutcome> = vec![];
     let mut outcome_receipt_models: Vec<models::execution_outcomes::ExecutionOutcomeReceipt> =
         vec![];
+    let mut gas_profile_models: Vec<models::execution_outcomes::ExecutionOutcomeGasProfile> =
+        vec![];
+    let mut proof_models: Vec<models::execution_outcomes::ExecutionOutcomeProof> = vec![];
     let mut receipts_cache_lock = receipts_cache_arc.lock().await;
     for (index_in_chunk, outcome) in execution_outcomes.iter().enumerate() {
         // Trying to take the parent Transaction hash for the Receipt from ReceiptsCache
@@ -53,6 +69,22 @@ pub async fn store_execution_outcomes_for_chunk(
         );
         outcome_models.push(model);
 
+        if options.store_proofs {
+            proof_models.push(
+                models::execution_outcomes::ExecutionOutcomeProof::from_execution_outcome(
+                    &outcome.execution_outcome,
+                ),
+            );
+        }
+
+        if options.store_gas_profiles {
+            gas_profile_models.extend(
+                models::execution_outcomes::ExecutionOutcomeGasProfile::from_execution_outcome(
+                    &outcome.execution_outcome,
+                ),
+            );
+        }
+
         outcome_receipt_models.extend(
             outcome
                 .execution_outcome
@@ -102,5 +134,31 @@ pub async fn store_execution_outcomes_for_chunk(
         &outcome_receipt_models
     );
 
+    for chunk in proof_models.chunks(CHUNK_SIZE_FOR_BATCH_INSERT) {
+        let proofs_chunk = chunk.to_owned();
+        crate::await_retry_or_panic!(
+            diesel::insert_into(schema::execution_outcome_proofs::table)
+                .values(proofs_chunk.clone())
+                .on_conflict_do_nothing()
+                .execute_async(pool),
+            5,
+            "ExecutionOutcomeProofs were stored in database".to_string(),
+            &proofs_chunk
+        );
+    }
+
+    for chunk in gas_profile_models.chunks(CHUNK_SIZE_FOR_BATCH_INSERT) {
+        let gas_profiles_chunk = chunk.to_owned();
+        crate::await_retry_or_panic!(
+            diesel::insert_into(schema::execution_outcome_gas_profiles::table)
+                .values(gas_profiles_chunk.clone())
+                .on_conflict_do_nothing()
+                .execute_async(pool),
+            5,
+            "ExecutionOutcomeGasProfiles were stored in database".to_string(),
+            &gas_profiles_chunk
+        );
+    }
+
     Ok(())
 }
@@ -1,13 +1,20 @@
 pub mod access_keys;
+pub mod account_balance_changes;
 pub mod account_changes;
 pub mod accounts;
 pub mod aggregated;
 pub mod assets;
+pub mod block_fees;
 pub mod blocks;
 pub mod chunks;
+pub mod contract_deployments;
+pub mod epochs;
 pub mod execution_outcomes;
 pub mod genesis;
 pub mod receipts;
+pub mod staking;
+#[cfg(test)]
+pub(crate) mod test_utils;
 pub mod transactions;
 
 const CHUNK_SIZE_FOR_BATCH_INSERT: usize = 500;
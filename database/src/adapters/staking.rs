@@ -0,0 +1,431 @@
+use std::collections::{HashMap, VecDeque};
+
+use actix_diesel::dsl::AsyncRunQueryDsl;
+use bigdecimal::BigDecimal;
+use diesel::sql_types::{Array, Nullable, Numeric, Text};
+use diesel::PgConnection;
+use tracing::warn;
+
+use near_indexer_primitives::views::{ActionView, ExecutionStatusView, ReceiptEnumView};
+
+use crate::adapters::assets::filter::ContractPattern;
+use crate::adapters::CHUNK_SIZE_FOR_BATCH_INSERT;
+use crate::models;
+use crate::models::enums::StakingEventKind;
+use crate::schema;
+
+/// The events the method of the reference staking pool reports, in the order of its logs.
+/// The other methods don't change the delegator's position
+fn method_event_kinds(method_name: &str) -> &'static [StakingEventKind] {
+    match method_name {
+        "deposit" => &[StakingEventKind::Deposit],
+        "deposit_and_stake" => &[StakingEventKind::Deposit, StakingEventKind::Stake],
+        "stake" | "stake_all" => &[StakingEventKind::Stake],
+        "unstake" | "unstake_all" => &[StakingEventKind::Unstake],
+        "withdraw" | "withdraw_all" => &[StakingEventKind::Withdraw],
+        _ => &[],
+    }
+}
+
+/// The delegator's position change reported by the staking pool in the logs
+#[derive(Debug)]
+struct StakingLog {
+    delegator_account_id: String,
+    event_kind: StakingEventKind,
+    amount: u128,
+    unstaked_balance: u128,
+    staking_shares: Option<u128>,
+}
+
+/// Saves the delegators' deposits, stakes, unstakes and withdrawals
+/// to the pools matching the given patterns, and updates their positions
+pub async fn store_staking_events(
+    pool: &actix_diesel::Database<PgConnection>,
+    shards: &[near_indexer_primitives::IndexerShard],
+    block_height: near_indexer_primitives::types::BlockHeight,
+    block_timestamp: u64,
+    staking_pools: &[ContractPattern],
+) -> anyhow::Result<()> {
+    if staking_pools.is_empty() {
+        return Ok(());
+    }
+
+    // Shard by shard, in the execution order of the receipts
+    let staking_event_models: Vec<models::staking::StakingEvent> = shards
+        .iter()
+        .flat_map(|shard| shard.receipt_execution_outcomes.iter())
+        .filter(|outcome| {
+            staking_pools
+                .iter()
+                .any(|pattern| pattern.matches(outcome.receipt.receiver_id.as_str()))
+        })
+        .flat_map(|outcome| compose_staking_events(outcome, block_height, block_timestamp))
+        .collect();
+
+    if staking_event_models.is_empty() {
+        return Ok(());
+    }
+
+    for chunk in staking_event_models.chunks(CHUNK_SIZE_FOR_BATCH_INSERT) {
+        let staking_events_chunk = chunk.to_owned();
+        crate::await_retry_or_panic!(
+            diesel::insert_into(schema::staking_events::table)
+                .values(staking_events_chunk.clone())
+                .on_conflict_do_nothing()
+                .execute_async(pool),
+            10,
+            "StakingEvents were stored to database".to_string(),
+            &staking_events_chunk
+        );
+    }
+
+    let position_models = fold_staking_positions(&staking_event_models, block_height);
+    for chunk in position_models.chunks(CHUNK_SIZE_FOR_BATCH_INSERT) {
+        let delegator_account_ids: Vec<String> = chunk
+            .iter()
+            .map(|position| position.delegator_account_id.clone())
+            .collect();
+        let pool_account_ids: Vec<String> = chunk
+            .iter()
+            .map(|position| position.pool_account_id.clone())
+            .collect();
+        let unstaked_balances: Vec<BigDecimal> = chunk
+            .iter()
+            .map(|position| position.unstaked_balance.clone())
+            .collect();
+        let staking_shares: Vec<Option<BigDecimal>> = chunk
+            .iter()
+            .map(|position| position.staking_shares.clone())
+            .collect();
+
+        // The block is applied once even if it is indexed again, the older blocks don't override the position.
+        // The shares are reported only for STAKE and UNSTAKE, so we keep the last known value
+        crate::await_retry_or_panic!(
+            diesel::sql_query(
+                r"
+                INSERT INTO staking_positions (
+                    delegator_account_id, pool_account_id, unstaked_balance, staking_shares, last_update_block_height
+                )
+                SELECT positions.*, $5
+                FROM unnest($1, $2, $3, $4)
+                    AS positions(delegator_account_id, pool_account_id, unstaked_balance, staking_shares)
+                ON CONFLICT (delegator_account_id, pool_account_id) DO UPDATE
+                SET unstaked_balance = excluded.unstaked_balance,
+                    staking_shares = COALESCE(excluded.staking_shares, staking_positions.staking_shares),
+                    last_update_block_height = excluded.last_update_block_height
+                WHERE staking_positions.last_update_block_height < excluded.last_update_block_height
+                ",
+            )
+            .bind::<Array<Text>, _>(delegator_account_ids.clone())
+            .bind::<Array<Text>, _>(pool_account_ids)
+            .bind::<Array<Numeric>, _>(unstaked_balances)
+            .bind::<Array<Nullable<Numeric>>, _>(staking_shares)
+            .bind::<Numeric, _>(BigDecimal::from(block_height))
+            .execute_async(pool),
+            10,
+            "StakingPositions were stored to database".to_string(),
+            &delegator_account_ids
+        );
+    }
+
+    Ok(())
+}
+
+/// The positions of the delegators in the pools after all the events of the block.
+/// The pool lives on one shard, so its events come in the execution order and the last one wins
+fn fold_staking_positions(
+    staking_events: &[models::staking::StakingEvent],
+    block_height: near_indexer_primitives::types::BlockHeight,
+) -> Vec<models::staking::StakingPosition> {
+    let mut positions: HashMap<(String, String), models::staking::StakingPosition> = HashMap::new();
+    for staking_event in staking_events {
+        let position = positions
+            .entry((
+                staking_event.delegator_account_id.clone(),
+                staking_event.pool_account_id.clone(),
+            ))
+            .or_insert_with(|| models::staking::StakingPosition {
+                delegator_account_id: staking_event.delegator_account_id.clone(),
+                pool_account_id: staking_event.pool_account_id.clone(),
+                unstaked_balance: BigDecimal::from(0),
+                staking_shares: None,
+                last_update_block_height: block_height.into(),
+            });
+        position.unstaked_balance = staking_event.unstaked_balance_after_event.clone();
+        if staking_event.staking_shares_after_event.is_some() {
+            position.staking_shares = staking_event.staking_shares_after_event.clone();
+        }
+    }
+    positions.into_values().collect()
+}
+
+fn compose_staking_events(
+    outcome: &near_indexer_primitives::IndexerExecutionOutcomeWithReceipt,
+    block_height: near_indexer_primitives::types::BlockHeight,
+    block_timestamp: u64,
+) -> Vec<models::staking::StakingEvent> {
+    // The logs of the failed receipts are stored, but the state changes are reverted
+    match outcome.execution_outcome.outcome.status {
+        ExecutionStatusView::SuccessValue(_) | ExecutionStatusView::SuccessReceiptId(_) => {}
+        ExecutionStatusView::Unknown | ExecutionStatusView::Failure(_) => return vec![],
+    }
+    // The batch receipt can call several methods, their logs follow one another.
+    // Each log is attributed to the first method which is expected to report such an event
+    let mut expected_events: VecDeque<(&str, StakingEventKind)> = match &outcome.receipt.receipt {
+        ReceiptEnumView::Action { actions, .. } => actions
+            .iter()
+            .flat_map(|action| match action {
+                ActionView::FunctionCall { method_name, .. } => method_event_kinds(method_name)
+                    .iter()
+                    .map(|event_kind| (method_name.as_str(), *event_kind))
+                    .collect::<Vec<_>>(),
+                _ => vec![],
+            })
+            .collect(),
+        ReceiptEnumView::Data { .. } => return vec![],
+    };
+
+    let mut staking_events = vec![];
+    for staking_log in outcome
+        .execution_outcome
+        .outcome
+        .logs
+        .iter()
+        .filter_map(|log| parse_staking_log(log))
+    {
+        let method_name = loop {
+            match expected_events.pop_front() {
+                Some((method_name, event_kind)) if event_kind == staking_log.event_kind => {
+                    break method_name
+                }
+                // The method could do nothing, e.g. `unstake_all` with nothing staked
+                Some(_) => continue,
+                None => {
+                    warn!(
+                        target: crate::EXPLORER_DATABASE,
+                        "Staking event {:?} in receipt {} does not match the called methods, skipping the rest of the receipt",
+                        staking_log.event_kind,
+                        outcome.receipt.receipt_id,
+                    );
+                    return staking_events;
+                }
+            }
+        };
+        staking_events.push(models::staking::StakingEvent {
+            receipt_id: outcome.receipt.receipt_id.to_string(),
+            index_in_receipt: staking_events.len() as i32,
+            executed_in_block_timestamp: block_timestamp.into(),
+            executed_in_block_height: block_height.into(),
+            pool_account_id: outcome.receipt.receiver_id.to_string(),
+            delegator_account_id: staking_log
+                .delegator_account_id
+                .escape_default()
+                .to_string(),
+            method_name: method_name.escape_default().to_string(),
+            event_kind: staking_log.event_kind,
+            amount: models::u128_to_big_decimal(staking_log.amount),
+            unstaked_balance_after_event: models::u128_to_big_decimal(staking_log.unstaked_balance),
+            staking_shares_after_event: staking_log.staking_shares.map(models::u128_to_big_decimal),
+        });
+    }
+    staking_events
+}
+
+/// Parses the logs of the reference staking pool implementation:
+/// - `@{account_id} deposited {amount}. New unstaked balance is {unstaked_balance}`
+/// - `@{account_id} staking {amount}. Received {shares} new staking shares. Total {unstaked_balance} unstaked balance and {staking_shares} staking shares`
+/// - `@{account_id} unstaking {amount}. Spent {shares} staking shares. Total {unstaked_balance} unstaked balance and {staking_shares} staking shares`
+/// - `@{account_id} withdrawing {amount}. New unstaked balance is {unstaked_balance}`
+fn parse_staking_log(log: &str) -> Option<StakingLog> {
+    let (delegator_account_id, message) = log.strip_prefix('@')?.split_once(' ')?;
+
+    let (event_kind, amount, unstaked_balance, staking_shares) =
+        if let Some(message) = message.strip_prefix("deposited ") {
+            let (amount, unstaked_balance) = message.split_once(". New unstaked balance is ")?;
+            (StakingEventKind::Deposit, amount, unstaked_balance, None)
+        } else if let Some(message) = message.strip_prefix("withdrawing ") {
+            let (amount, unstaked_balance) = message.split_once(". New unstaked balance is ")?;
+            (StakingEventKind::Withdraw, amount, unstaked_balance, None)
+        } else if let Some(message) = message.strip_prefix("staking ") {
+            let (amount, message) = message.split_once(". Received ")?;
+            let (_, totals) = message.split_once(" new staking shares. Total ")?;
+            let (unstaked_balance, staking_shares) = parse_totals(totals)?;
+            (
+                StakingEventKind::Stake,
+                amount,
+                unstaked_balance,
+                Some(staking_shares),
+            )
+        } else if let Some(message) = message.strip_prefix("unstaking ") {
+            let (amount, message) = message.split_once(". Spent ")?;
+            let (_, totals) = message.split_once(" staking shares. Total ")?;
+            let (unstaked_balance, staking_shares) = parse_totals(totals)?;
+            (
+                StakingEventKind::Unstake,
+                amount,
+                unstaked_balance,
+                Some(staking_shares),
+            )
+        } else {
+            return None;
+        };
+
+    Some(StakingLog {
+        delegator_account_id: delegator_account_id.to_string(),
+        event_kind,
+        amount: amount.parse().ok()?,
+        unstaked_balance: unstaked_balance.parse().ok()?,
+        staking_shares: match staking_shares {
+            Some(staking_shares) => Some(staking_shares.parse().ok()?),
+            None => None,
+        },
+    })
+}
+
+/// `{unstaked_balance} unstaked balance and {staking_shares} staking shares`
+fn parse_totals(totals: &str) -> Option<(&str, &str)> {
+    let (unstaked_balance, staking_shares) = totals.split_once(" unstaked balance and ")?;
+    Some((
+        unstaked_balance,
+        staking_shares.strip_suffix(" staking shares")?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn function_call(method_name: &str) -> ActionView {
+        ActionView::FunctionCall {
+            method_name: method_name.to_string(),
+            args: vec![].into(),
+            gas: 30_000_000_000_000,
+            deposit: 0,
+        }
+    }
+
+    fn compose(methods: &[&str], logs: Vec<&str>) -> Vec<models::staking::StakingEvent> {
+        let outcome = crate::adapters::test_utils::function_call_outcome(
+            "alice.near",
+            "pool.poolv1.near",
+            methods.iter().map(|method| function_call(method)).collect(),
+            logs,
+            ExecutionStatusView::SuccessValue(vec![]),
+        );
+        compose_staking_events(&outcome, 1, 1)
+    }
+
+    #[test]
+    fn parses_reference_pool_logs() {
+        let deposit =
+            parse_staking_log("@alice.near deposited 1000. New unstaked balance is 1500").unwrap();
+        assert_eq!(deposit.delegator_account_id, "alice.near");
+        assert_eq!(deposit.event_kind, StakingEventKind::Deposit);
+        assert_eq!((deposit.amount, deposit.unstaked_balance), (1000, 1500));
+        assert_eq!(deposit.staking_shares, None);
+
+        let stake = parse_staking_log(
+            "@alice.near staking 1000. Received 900 new staking shares. Total 500 unstaked balance and 2700 staking shares",
+        )
+        .unwrap();
+        assert_eq!(stake.event_kind, StakingEventKind::Stake);
+        assert_eq!((stake.amount, stake.unstaked_balance), (1000, 500));
+        assert_eq!(stake.staking_shares, Some(2700));
+
+        let unstake = parse_staking_log(
+            "@alice.near unstaking 500. Spent 450 staking shares. Total 1000 unstaked balance and 2250 staking shares",
+        )
+        .unwrap();
+        assert_eq!(unstake.event_kind, StakingEventKind::Unstake);
+        assert_eq!((unstake.amount, unstake.unstaked_balance), (500, 1000));
+        assert_eq!(unstake.staking_shares, Some(2250));
+
+        let withdraw =
+            parse_staking_log("@alice.near withdrawing 1000. New unstaked balance is 0").unwrap();
+        assert_eq!(withdraw.event_kind, StakingEventKind::Withdraw);
+        assert_eq!((withdraw.amount, withdraw.unstaked_balance), (1000, 0));
+    }
+
+    #[test]
+    fn ignores_other_logs() {
+        assert!(parse_staking_log(
+            "Epoch 1000: Contract received total rewards of 10 tokens. New total staked balance is 100"
+        )
+        .is_none());
+        assert!(
+            parse_staking_log("@alice.near deposited many. New unstaked balance is 0").is_none()
+        );
+        assert!(
+            parse_staking_log("@alice.near staking 1000. Received 900 new staking shares")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn attributes_logs_to_every_method_of_the_batch() {
+        let events = compose(
+            &["deposit_and_stake", "ping", "unstake"],
+            vec![
+                "@alice.near deposited 1000. New unstaked balance is 1000",
+                "@alice.near staking 1000. Received 900 new staking shares. Total 0 unstaked balance and 900 staking shares",
+                "Epoch 1000: Contract received total rewards of 10 tokens. New total staked balance is 100",
+                "@alice.near unstaking 500. Spent 450 staking shares. Total 500 unstaked balance and 450 staking shares",
+            ],
+        );
+        let events: Vec<_> = events
+            .iter()
+            .map(|event| {
+                (
+                    event.index_in_receipt,
+                    event.method_name.as_str(),
+                    event.event_kind,
+                )
+            })
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (0, "deposit_and_stake", StakingEventKind::Deposit),
+                (1, "deposit_and_stake", StakingEventKind::Stake),
+                (2, "unstake", StakingEventKind::Unstake),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_failed_and_not_staking_receipts() {
+        let log = "@alice.near deposited 1000. New unstaked balance is 1000";
+        assert!(compose(&["get_account"], vec![log]).is_empty());
+
+        let outcome = crate::adapters::test_utils::function_call_outcome(
+            "alice.near",
+            "pool.poolv1.near",
+            vec![function_call("deposit")],
+            vec![log],
+            ExecutionStatusView::Failure(
+                near_primitives::errors::TxExecutionError::InvalidTxError(
+                    near_primitives::errors::InvalidTxError::InvalidSignature,
+                ),
+            ),
+        );
+        assert!(compose_staking_events(&outcome, 1, 1).is_empty());
+    }
+
+    #[test]
+    fn folds_the_events_of_the_block_into_the_last_position() {
+        let events = compose(
+            &["unstake", "withdraw"],
+            vec![
+                "@alice.near unstaking 500. Spent 450 staking shares. Total 500 unstaked balance and 450 staking shares",
+                "@alice.near withdrawing 500. New unstaked balance is 0",
+            ],
+        );
+        let positions = fold_staking_positions(&events, 1);
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].delegator_account_id, "alice.near");
+        assert_eq!(positions[0].pool_account_id, "pool.poolv1.near");
+        assert_eq!(positions[0].unstaked_balance, BigDecimal::from(0));
+        // WITHDRAW does not report the shares, the value from UNSTAKE is kept
+        assert_eq!(positions[0].staking_shares, Some(BigDecimal::from(450)));
+    }
+}
@@ -0,0 +1,45 @@
+use near_indexer_primitives::views;
+
+/// Execution outcome of the action receipt in the shape NEAR Lake gives it to us,
+/// only the fields the events extraction relies on are meaningful
+pub(crate) fn function_call_outcome(
+    predecessor_id: &str,
+    receiver_id: &str,
+    actions: Vec<views::ActionView>,
+    logs: Vec<&str>,
+    status: views::ExecutionStatusView,
+) -> near_indexer_primitives::IndexerExecutionOutcomeWithReceipt {
+    let receipt_id = near_indexer_primitives::CryptoHash::default();
+    near_indexer_primitives::IndexerExecutionOutcomeWithReceipt {
+        execution_outcome: views::ExecutionOutcomeWithIdView {
+            proof: vec![],
+            block_hash: near_indexer_primitives::CryptoHash::default(),
+            id: receipt_id,
+            outcome: views::ExecutionOutcomeView {
+                logs: logs.into_iter().map(String::from).collect(),
+                receipt_ids: vec![],
+                gas_burnt: 0,
+                tokens_burnt: 0,
+                executor_id: receiver_id.parse().unwrap(),
+                status,
+                metadata: views::ExecutionMetadataView {
+                    version: 1,
+                    gas_profile: None,
+                },
+            },
+        },
+        receipt: views::ReceiptView {
+            predecessor_id: predecessor_id.parse().unwrap(),
+            receiver_id: receiver_id.parse().unwrap(),
+            receipt_id,
+            receipt: views::ReceiptEnumView::Action {
+                signer_id: predecessor_id.parse().unwrap(),
+                signer_public_key: near_crypto::PublicKey::empty(near_crypto::KeyType::ED25519),
+                gas_price: 0,
+                output_data_receivers: vec![],
+                input_data_ids: vec![],
+                actions,
+            },
+        },
+    }
+}
@@ -1,8 +1,9 @@
 use bigdecimal::BigDecimal;
 
-use crate::models::enums::AccessKeyPermission;
+use crate::models::enums::{AccessKeyHistoryAction, AccessKeyPermission};
+use crate::models::serializers::AccessKeyPermissionView;
 use crate::schema;
-use schema::access_keys;
+use schema::{access_key_history, access_keys};
 
 #[derive(Insertable, Clone, Debug)]
 pub struct AccessKey {
@@ -12,6 +13,10 @@ pub struct AccessKey {
     pub deleted_by_receipt_id: Option<String>,
     pub permission_kind: AccessKeyPermission,
     pub last_update_block_height: BigDecimal,
+    pub permission_allowance: Option<BigDecimal>,
+    pub permission_receiver_id: Option<String>,
+    pub permission_method_names: Option<Vec<String>>,
+    pub nonce: Option<BigDecimal>,
 }
 
 impl AccessKey {
@@ -22,6 +27,8 @@ impl AccessKey {
         create_by_receipt_id: &near_indexer_primitives::CryptoHash,
         last_update_block_height: near_indexer_primitives::types::BlockHeight,
     ) -> Self {
+        let (permission_allowance, permission_receiver_id, permission_method_names) =
+            permission_details(access_key.permission.clone().into());
         Self {
             public_key: public_key.to_string(),
             account_id: account_id.to_string(),
@@ -29,6 +36,10 @@ impl AccessKey {
             deleted_by_receipt_id: None,
             permission_kind: (&access_key.permission).into(),
             last_update_block_height: last_update_block_height.into(),
+            permission_allowance,
+            permission_receiver_id,
+            permission_method_names,
+            nonce: Some(access_key.nonce.into()),
         }
     }
 
@@ -38,6 +49,13 @@ impl AccessKey {
         access_key: &near_primitives::account::AccessKey,
         last_update_block_height: near_indexer_primitives::types::BlockHeight,
     ) -> Self {
+        let (permission_allowance, permission_receiver_id, permission_method_names) =
+            permission_details(
+                near_indexer_primitives::views::AccessKeyPermissionView::from(
+                    access_key.permission.clone(),
+                )
+                .into(),
+            );
         Self {
             public_key: public_key.to_string(),
             account_id: account_id.to_string(),
@@ -45,6 +63,90 @@ impl AccessKey {
             deleted_by_receipt_id: None,
             permission_kind: (&access_key.permission).into(),
             last_update_block_height: last_update_block_height.into(),
+            permission_allowance,
+            permission_receiver_id,
+            permission_method_names,
+            nonce: Some(access_key.nonce.into()),
         }
     }
 }
+
+#[derive(Insertable, Clone, Debug)]
+#[table_name = "access_key_history"]
+pub struct AccessKeyHistory {
+    pub public_key: String,
+    pub account_id: String,
+    pub caused_by_receipt_id: String,
+    pub index_in_receipt: i32,
+    pub changed_in_block_height: BigDecimal,
+    pub action: AccessKeyHistoryAction,
+    pub permission_kind: Option<AccessKeyPermission>,
+    pub permission_allowance: Option<BigDecimal>,
+    pub permission_receiver_id: Option<String>,
+    pub permission_method_names: Option<Vec<String>>,
+    pub nonce: Option<BigDecimal>,
+}
+
+impl AccessKeyHistory {
+    /// ADD or PERMISSION_CHANGE row with the permission of the key added by the receipt
+    pub fn from_access_key(
+        access_key: &AccessKey,
+        action: AccessKeyHistoryAction,
+        index_in_receipt: i32,
+    ) -> Option<Self> {
+        Some(Self {
+            public_key: access_key.public_key.clone(),
+            account_id: access_key.account_id.clone(),
+            caused_by_receipt_id: access_key.created_by_receipt_id.clone()?,
+            index_in_receipt,
+            changed_in_block_height: access_key.last_update_block_height.clone(),
+            action,
+            permission_kind: Some(access_key.permission_kind.clone()),
+            permission_allowance: access_key.permission_allowance.clone(),
+            permission_receiver_id: access_key.permission_receiver_id.clone(),
+            permission_method_names: access_key.permission_method_names.clone(),
+            nonce: access_key.nonce.clone(),
+        })
+    }
+
+    pub fn new_deleted(
+        public_key: &near_crypto::PublicKey,
+        account_id: &near_indexer_primitives::types::AccountId,
+        deleted_by_receipt_id: &near_indexer_primitives::CryptoHash,
+        index_in_receipt: i32,
+        changed_in_block_height: near_indexer_primitives::types::BlockHeight,
+    ) -> Self {
+        Self {
+            public_key: public_key.to_string(),
+            account_id: account_id.to_string(),
+            caused_by_receipt_id: deleted_by_receipt_id.to_string(),
+            index_in_receipt,
+            changed_in_block_height: changed_in_block_height.into(),
+            action: AccessKeyHistoryAction::Delete,
+            permission_kind: None,
+            permission_allowance: None,
+            permission_receiver_id: None,
+            permission_method_names: None,
+            nonce: None,
+        }
+    }
+}
+
+/// Splits FunctionCall permission into allowance, receiver_id and method_names columns,
+/// reusing the escaping we apply when the permission is stored as JSON in the actions
+fn permission_details(
+    permission: AccessKeyPermissionView,
+) -> (Option<BigDecimal>, Option<String>, Option<Vec<String>>) {
+    match permission {
+        AccessKeyPermissionView::FunctionCall {
+            allowance,
+            receiver_id,
+            method_names,
+        } => (
+            allowance.map(crate::models::u128_to_big_decimal),
+            Some(receiver_id),
+            Some(method_names),
+        ),
+        AccessKeyPermissionView::FullAccess => (None, None, None),
+    }
+}
@@ -0,0 +1,82 @@
+use bigdecimal::BigDecimal;
+
+use near_indexer_primitives::views::{StateChangeCauseView, StateChangeValueView};
+
+use crate::models::enums::StateChangeReasonKind;
+use crate::schema;
+use schema::account_balance_changes;
+
+#[derive(Insertable, Queryable, QueryableByName, Clone, Debug)]
+#[table_name = "account_balance_changes"]
+pub struct AccountBalanceChange {
+    pub account_id: String,
+    pub changed_in_block_height: BigDecimal,
+    pub changed_in_block_timestamp: BigDecimal,
+    pub index_in_block: i32,
+    pub caused_by_receipt_id: Option<String>,
+    pub caused_by_transaction_hash: Option<String>,
+    pub update_reason: StateChangeReasonKind,
+    pub nonstaked_balance: BigDecimal,
+    pub staked_balance: BigDecimal,
+    pub storage_usage: BigDecimal,
+}
+
+impl AccountBalanceChange {
+    /// Returns `None` for all the state changes except `AccountUpdate`.
+    /// `index_in_block` is set to -1, it is assigned once the changes of the block are deduplicated
+    pub fn from_state_change_with_cause(
+        state_change_with_cause: &near_indexer_primitives::views::StateChangeWithCauseView,
+        changed_in_block_height: near_indexer_primitives::types::BlockHeight,
+        changed_in_block_timestamp: u64,
+    ) -> Option<Self> {
+        let near_indexer_primitives::views::StateChangeWithCauseView { cause, value } =
+            state_change_with_cause;
+
+        let (account_id, account) = match value {
+            StateChangeValueView::AccountUpdate {
+                account_id,
+                account,
+            } => (account_id, account),
+            _ => return None,
+        };
+
+        Some(Self {
+            account_id: account_id.to_string(),
+            changed_in_block_height: changed_in_block_height.into(),
+            changed_in_block_timestamp: changed_in_block_timestamp.into(),
+            index_in_block: -1,
+            caused_by_receipt_id: match cause {
+                StateChangeCauseView::ActionReceiptProcessingStarted { receipt_hash }
+                | StateChangeCauseView::ActionReceiptGasReward { receipt_hash }
+                | StateChangeCauseView::ReceiptProcessing { receipt_hash }
+                | StateChangeCauseView::PostponedReceipt { receipt_hash } => {
+                    Some(receipt_hash.to_string())
+                }
+                _ => None,
+            },
+            caused_by_transaction_hash: match cause {
+                StateChangeCauseView::TransactionProcessing { tx_hash } => {
+                    Some(tx_hash.to_string())
+                }
+                _ => None,
+            },
+            update_reason: cause.into(),
+            nonstaked_balance: crate::models::u128_to_big_decimal(account.amount),
+            staked_balance: crate::models::u128_to_big_decimal(account.locked),
+            storage_usage: account.storage_usage.into(),
+        })
+    }
+
+    /// The receipt or the transaction which caused the change
+    pub(crate) fn cause_id(&self) -> Option<&str> {
+        self.caused_by_receipt_id
+            .as_deref()
+            .or(self.caused_by_transaction_hash.as_deref())
+    }
+
+    pub(crate) fn has_same_balances(&self, other: &Self) -> bool {
+        self.nonstaked_balance == other.nonstaked_balance
+            && self.staked_balance == other.staked_balance
+            && self.storage_usage == other.storage_usage
+    }
+}
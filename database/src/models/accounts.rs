@@ -1,7 +1,8 @@
 use bigdecimal::BigDecimal;
 
+use crate::models::enums::AccountKind;
 use crate::schema;
-use schema::accounts;
+use schema::{account_lifecycles, account_state, accounts};
 
 #[derive(Insertable, Debug, Clone, QueryableByName)]
 #[table_name = "accounts"]
@@ -10,6 +11,8 @@ pub struct Account {
     pub created_by_receipt_id: Option<String>,
     pub deleted_by_receipt_id: Option<String>,
     pub last_update_block_height: BigDecimal,
+    pub account_kind: AccountKind,
+    pub parent_account_id: Option<String>,
 }
 
 impl Account {
@@ -18,11 +21,14 @@ impl Account {
         created_by_receipt_id: &near_indexer_primitives::CryptoHash,
         last_update_block_height: near_indexer_primitives::types::BlockHeight,
     ) -> Self {
+        let (account_kind, parent_account_id) = classify_account_id(account_id.as_str());
         Self {
             account_id: account_id.to_string(),
             created_by_receipt_id: Some(created_by_receipt_id.to_string()),
             deleted_by_receipt_id: None,
             last_update_block_height: last_update_block_height.into(),
+            account_kind,
+            parent_account_id,
         }
     }
 
@@ -30,11 +36,230 @@ impl Account {
         account_id: &near_indexer_primitives::types::AccountId,
         last_update_block_height: near_indexer_primitives::types::BlockHeight,
     ) -> Self {
+        let (account_kind, parent_account_id) = classify_account_id(account_id.as_str());
         Self {
             account_id: account_id.to_string(),
             created_by_receipt_id: None,
             deleted_by_receipt_id: None,
             last_update_block_height: last_update_block_height.into(),
+            account_kind,
+            parent_account_id,
+        }
+    }
+}
+
+/// Classifies the account by its id and returns the parent account id for the sub-accounts.
+/// Keep in sync with `classify_account_kind` SQL function from `2023-06-05-100000_account_kind` migration
+pub fn classify_account_id(account_id: &str) -> (AccountKind, Option<String>) {
+    let is_lowercase_hex = |value: &str| {
+        value
+            .chars()
+            .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+    };
+
+    if account_id.len() == 64 && is_lowercase_hex(account_id) {
+        return (AccountKind::NearImplicit, None);
+    }
+    if let Some(address) = account_id.strip_prefix("0x") {
+        if address.len() == 40 && is_lowercase_hex(address) {
+            return (AccountKind::EthImplicit, None);
+        }
+    }
+    match account_id.split_once('.') {
+        Some((_, parent_account_id)) => {
+            (AccountKind::SubAccount, Some(parent_account_id.to_string()))
+        }
+        None => (AccountKind::Named, None),
+    }
+}
+
+#[derive(Insertable, Queryable, Debug, Clone)]
+#[table_name = "account_state"]
+pub struct AccountState {
+    pub account_id: String,
+    pub nonstaked_balance: BigDecimal,
+    pub staked_balance: BigDecimal,
+    pub storage_usage: BigDecimal,
+    pub code_hash: String,
+    pub is_deleted: bool,
+    pub last_update_block_height: BigDecimal,
+}
+
+impl AccountState {
+    pub fn from_account_view(
+        account_id: &near_indexer_primitives::types::AccountId,
+        account: &near_indexer_primitives::views::AccountView,
+        last_update_block_height: near_indexer_primitives::types::BlockHeight,
+    ) -> Self {
+        Self {
+            account_id: account_id.to_string(),
+            nonstaked_balance: crate::models::u128_to_big_decimal(account.amount),
+            staked_balance: crate::models::u128_to_big_decimal(account.locked),
+            storage_usage: account.storage_usage.into(),
+            code_hash: account.code_hash.to_string(),
+            is_deleted: false,
+            last_update_block_height: last_update_block_height.into(),
+        }
+    }
+
+    pub fn new_deleted(
+        account_id: &near_indexer_primitives::types::AccountId,
+        last_update_block_height: near_indexer_primitives::types::BlockHeight,
+    ) -> Self {
+        Self {
+            account_id: account_id.to_string(),
+            nonstaked_balance: BigDecimal::from(0),
+            staked_balance: BigDecimal::from(0),
+            storage_usage: BigDecimal::from(0),
+            code_hash: near_indexer_primitives::CryptoHash::default().to_string(),
+            is_deleted: true,
+            last_update_block_height: last_update_block_height.into(),
+        }
+    }
+}
+
+#[derive(Insertable, Queryable, Debug, Clone, PartialEq)]
+#[table_name = "account_lifecycles"]
+pub struct AccountLifecycle {
+    pub account_id: String,
+    pub created_by_receipt_id: Option<String>,
+    pub created_in_block_height: Option<BigDecimal>,
+    pub deleted_by_receipt_id: Option<String>,
+    pub deleted_in_block_height: Option<BigDecimal>,
+}
+
+impl AccountLifecycle {
+    pub fn new_from_receipt(
+        account_id: &near_indexer_primitives::types::AccountId,
+        created_by_receipt_id: &near_indexer_primitives::CryptoHash,
+        created_in_block_height: near_indexer_primitives::types::BlockHeight,
+    ) -> Self {
+        Self {
+            account_id: account_id.to_string(),
+            created_by_receipt_id: Some(created_by_receipt_id.to_string()),
+            created_in_block_height: Some(created_in_block_height.into()),
+            deleted_by_receipt_id: None,
+            deleted_in_block_height: None,
+        }
+    }
+
+    pub fn new_from_genesis(account: &Account) -> Self {
+        Self {
+            account_id: account.account_id.clone(),
+            created_by_receipt_id: None,
+            created_in_block_height: Some(account.last_update_block_height.clone()),
+            deleted_by_receipt_id: None,
+            deleted_in_block_height: None,
+        }
+    }
+
+    /// The account was created before the indexing start, we know only when it was deleted
+    pub fn new_deleted(
+        account_id: &near_indexer_primitives::types::AccountId,
+        deleted_by_receipt_id: &near_indexer_primitives::CryptoHash,
+        deleted_in_block_height: near_indexer_primitives::types::BlockHeight,
+    ) -> Self {
+        Self {
+            account_id: account_id.to_string(),
+            created_by_receipt_id: None,
+            created_in_block_height: None,
+            deleted_by_receipt_id: Some(deleted_by_receipt_id.to_string()),
+            deleted_in_block_height: Some(deleted_in_block_height.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel::sql_types::Text;
+    use diesel::{Connection, PgConnection, RunQueryDsl};
+
+    use super::*;
+
+    sql_function!(fn classify_account_kind(account_id: Text) -> crate::models::enums::Account_kind);
+    sql_function!(fn classify_parent_account_id(account_id: Text) -> diesel::sql_types::Nullable<Text>);
+
+    /// Shared by the Rust and the SQL classification
+    const ACCOUNT_ID_FIXTURES: &[(&str, AccountKind, Option<&str>)] = &[
+        ("near", AccountKind::Named, None),
+        ("system", AccountKind::Named, None),
+        ("alice.near", AccountKind::SubAccount, Some("near")),
+        (
+            "app.alice.near",
+            AccountKind::SubAccount,
+            Some("alice.near"),
+        ),
+        (
+            "98793cd91a3f870fb126f66285808c7e094afcfc4eda8a970f6648cdf0dbd6de",
+            AccountKind::NearImplicit,
+            None,
+        ),
+        // 63 characters
+        (
+            "98793cd91a3f870fb126f66285808c7e094afcfc4eda8a970f6648cdf0dbd6d",
+            AccountKind::Named,
+            None,
+        ),
+        (
+            "98793cd91a3f870fb126f66285808c7e094afcfc4eda8a970f6648cdf0dbd6de.near",
+            AccountKind::SubAccount,
+            Some("near"),
+        ),
+        (
+            "0x32400084c286cf3e17e7b677ea9583e60a000324",
+            AccountKind::EthImplicit,
+            None,
+        ),
+        // 39 hex characters after `0x`
+        (
+            "0x32400084c286cf3e17e7b677ea9583e60a00032",
+            AccountKind::Named,
+            None,
+        ),
+        (
+            "0x32400084c286cf3e17e7b677ea9583e60a000324.near",
+            AccountKind::SubAccount,
+            Some("near"),
+        ),
+        // `g` is not a hex character
+        (
+            "0x32400084c286cf3e17e7b677ea9583e60a00032g",
+            AccountKind::Named,
+            None,
+        ),
+    ];
+
+    #[test]
+    fn classifies_account_ids() {
+        for (account_id, account_kind, parent_account_id) in ACCOUNT_ID_FIXTURES {
+            assert_eq!(
+                classify_account_id(account_id),
+                (*account_kind, parent_account_id.map(String::from)),
+                "{}",
+                account_id
+            );
+        }
+    }
+
+    /// The backfill of the migration has to classify the accounts the same way
+    #[test]
+    #[ignore = "requires DATABASE_URL with the migrations applied"]
+    fn sql_classification_matches() {
+        let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL is required");
+        let conn = PgConnection::establish(&database_url).unwrap();
+        for (account_id, account_kind, parent_account_id) in ACCOUNT_ID_FIXTURES {
+            let sql_classification: (AccountKind, Option<String>) = diesel::select((
+                classify_account_kind(*account_id),
+                classify_parent_account_id(*account_id),
+            ))
+            .get_result(&conn)
+            .unwrap();
+            assert_eq!(
+                sql_classification,
+                (*account_kind, parent_account_id.map(String::from)),
+                "{}",
+                account_id
+            );
         }
     }
 }
@@ -0,0 +1,17 @@
+use bigdecimal::BigDecimal;
+
+use crate::models::enums::ActionKind;
+use crate::schema;
+use schema::aggregated__account_interactions;
+
+#[derive(Insertable, Queryable, Clone, Debug)]
+#[table_name = "aggregated__account_interactions"]
+pub struct AccountInteraction {
+    pub predecessor_account_id: String,
+    pub receiver_account_id: String,
+    pub day_timestamp: BigDecimal,
+    pub action_kind: ActionKind,
+    pub actions_count: i64,
+    pub total_deposit: BigDecimal,
+    pub total_gas_burnt: BigDecimal,
+}
@@ -0,0 +1,25 @@
+use bigdecimal::BigDecimal;
+
+use crate::schema;
+use schema::{aggregated__contract_usage, aggregated__contract_usage_callers};
+
+#[derive(Insertable, Queryable, Clone, Debug)]
+#[table_name = "aggregated__contract_usage"]
+pub struct ContractUsage {
+    pub contract_account_id: String,
+    pub day_timestamp: BigDecimal,
+    pub method_name: String,
+    pub calls_count: i64,
+    pub unique_callers_count: i64,
+    pub total_gas_burnt: BigDecimal,
+    pub total_deposit: BigDecimal,
+}
+
+#[derive(Insertable, Queryable, Clone, Debug)]
+#[table_name = "aggregated__contract_usage_callers"]
+pub struct ContractUsageCaller {
+    pub contract_account_id: String,
+    pub day_timestamp: BigDecimal,
+    pub method_name: String,
+    pub caller_account_id: String,
+}
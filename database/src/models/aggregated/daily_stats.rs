@@ -0,0 +1,17 @@
+use bigdecimal::BigDecimal;
+
+use crate::schema;
+use schema::aggregated__daily_stats;
+
+#[derive(Insertable, Queryable, Clone, Debug)]
+#[table_name = "aggregated__daily_stats"]
+pub struct DailyStats {
+    pub day_timestamp: BigDecimal,
+    pub transactions_count: i64,
+    pub active_accounts_count: i64,
+    pub new_accounts_count: i64,
+    pub deleted_accounts_count: i64,
+    pub gas_used: BigDecimal,
+    pub tokens_burnt: BigDecimal,
+    pub deployed_contracts_count: i64,
+}
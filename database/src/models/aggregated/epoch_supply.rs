@@ -0,0 +1,19 @@
+use bigdecimal::BigDecimal;
+
+use crate::schema;
+use schema::aggregated__epoch_supply;
+
+#[derive(Insertable, Queryable, Clone, Debug)]
+#[table_name = "aggregated__epoch_supply"]
+pub struct EpochSupply {
+    pub epoch_id: String,
+    pub epoch_height: Option<BigDecimal>,
+    pub start_block_height: BigDecimal,
+    pub start_block_timestamp: BigDecimal,
+    pub end_block_height: BigDecimal,
+    pub end_block_timestamp: BigDecimal,
+    pub total_supply_before: BigDecimal,
+    pub total_supply_after: BigDecimal,
+    pub minted_tokens: BigDecimal,
+    pub burnt_tokens: BigDecimal,
+}
@@ -1 +1,5 @@
+pub mod account_interactions;
 pub mod circulating_supply;
+pub mod contract_usage;
+pub mod daily_stats;
+pub mod epoch_supply;
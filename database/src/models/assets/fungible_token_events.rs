@@ -17,4 +17,5 @@ pub struct FungibleTokenEvent {
     pub token_old_owner_account_id: String,
     pub token_new_owner_account_id: String,
     pub event_memo: String,
+    pub derived_from_function_call: bool,
 }
@@ -0,0 +1,19 @@
+use bigdecimal::BigDecimal;
+
+use crate::models::enums::EventInvalidityReason;
+use crate::schema;
+use schema::assets__invalid_events;
+
+#[derive(Insertable, Queryable, Clone, Debug)]
+#[table_name = "assets__invalid_events"]
+pub struct InvalidEvent {
+    pub emitted_for_receipt_id: String,
+    pub emitted_at_block_timestamp: BigDecimal,
+    pub emitted_in_shard_id: BigDecimal,
+    pub emitted_index_of_event_entry_in_shard: i32,
+    pub emitted_by_contract_account_id: String,
+    pub event_standard: String,
+    pub event_kind: String,
+    pub event_data: serde_json::Value,
+    pub invalidity_reason: EventInvalidityReason,
+}
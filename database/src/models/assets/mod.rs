@@ -1,2 +1,4 @@
 pub(crate) mod fungible_token_events;
+pub(crate) mod invalid_events;
+pub(crate) mod multi_token_events;
 pub(crate) mod non_fungible_token_events;
@@ -0,0 +1,157 @@
+use bigdecimal::BigDecimal;
+
+use crate::models::u128_to_big_decimal;
+use crate::schema;
+use schema::block_fees;
+
+/// The share of the gas burnt by the contract execution which is given to the
+/// called contract, it is `burnt_gas_reward` of the runtime config
+const CONTRACT_REWARD_NUMERATOR: u64 = 3;
+const CONTRACT_REWARD_DENOMINATOR: u64 = 10;
+
+#[derive(Insertable, Queryable, Clone, Debug)]
+#[table_name = "block_fees"]
+pub struct BlockFees {
+    pub block_height: BigDecimal,
+    pub block_hash: String,
+    pub block_timestamp: BigDecimal,
+    pub gas_price: BigDecimal,
+    pub gas_used: BigDecimal,
+    pub transactions_tokens_burnt: BigDecimal,
+    pub receipts_tokens_burnt: BigDecimal,
+    pub tokens_burnt: BigDecimal,
+    pub contract_reward: BigDecimal,
+}
+
+impl BlockFees {
+    pub fn new(
+        block: &near_indexer_primitives::views::BlockView,
+        shards: &[near_indexer_primitives::IndexerShard],
+    ) -> Self {
+        let chunks = shards.iter().filter_map(|shard| shard.chunk.as_ref());
+        let gas_used: u64 = chunks.clone().map(|chunk| chunk.header.gas_used).sum();
+        let transactions_tokens_burnt: u128 = chunks
+            .flat_map(|chunk| chunk.transactions.iter())
+            .map(|transaction| transaction.outcome.execution_outcome.outcome.tokens_burnt)
+            .sum();
+
+        let receipt_outcomes = shards
+            .iter()
+            .flat_map(|shard| shard.receipt_execution_outcomes.iter())
+            .map(|outcome| &outcome.execution_outcome.outcome);
+        let receipts_tokens_burnt: u128 = receipt_outcomes
+            .clone()
+            .map(|outcome| outcome.tokens_burnt)
+            .sum();
+        let contract_reward: u128 = receipt_outcomes.map(contract_reward).sum();
+
+        Self {
+            block_height: block.header.height.into(),
+            block_hash: block.header.hash.to_string(),
+            block_timestamp: block.header.timestamp.into(),
+            gas_price: u128_to_big_decimal(block.header.gas_price),
+            gas_used: gas_used.into(),
+            transactions_tokens_burnt: u128_to_big_decimal(transactions_tokens_burnt),
+            receipts_tokens_burnt: u128_to_big_decimal(receipts_tokens_burnt),
+            tokens_burnt: u128_to_big_decimal(transactions_tokens_burnt + receipts_tokens_burnt),
+            contract_reward: u128_to_big_decimal(contract_reward),
+        }
+    }
+}
+
+/// The tokens given to the called contract for the receipt execution. The gas burnt by
+/// the contract is taken from the gas profile, the gas price is the one the outcome
+/// `tokens_burnt` are charged with (refunds burn gas without charging any tokens)
+fn contract_reward(outcome: &near_indexer_primitives::views::ExecutionOutcomeView) -> u128 {
+    if outcome.gas_burnt == 0 {
+        return 0;
+    }
+    let contract_gas_burnt: u64 = outcome
+        .metadata
+        .gas_profile
+        .iter()
+        .flatten()
+        .filter(|cost| cost.cost_category == "WASM_HOST_COST")
+        .map(|cost| cost.gas_used)
+        .sum();
+    let gas_price = outcome.tokens_burnt / u128::from(outcome.gas_burnt);
+    let reward_gas = contract_gas_burnt * CONTRACT_REWARD_NUMERATOR / CONTRACT_REWARD_DENOMINATOR;
+    u128::from(reward_gas) * gas_price
+}
+
+#[cfg(test)]
+mod tests {
+    use near_indexer_primitives::views;
+
+    use super::*;
+    use crate::adapters::test_utils::function_call_outcome;
+
+    fn cost(cost_category: &str, cost: &str, gas_used: u64) -> views::CostGasUsed {
+        views::CostGasUsed {
+            cost_category: cost_category.to_string(),
+            cost: cost.to_string(),
+            gas_used,
+        }
+    }
+
+    fn outcome(
+        gas_burnt: u64,
+        tokens_burnt: u128,
+        gas_profile: Option<Vec<views::CostGasUsed>>,
+    ) -> views::ExecutionOutcomeView {
+        let mut outcome = function_call_outcome(
+            "alice.near",
+            "contract.near",
+            vec![],
+            vec![],
+            views::ExecutionStatusView::SuccessValue(vec![]),
+        )
+        .execution_outcome
+        .outcome;
+        outcome.gas_burnt = gas_burnt;
+        outcome.tokens_burnt = tokens_burnt;
+        outcome.metadata = views::ExecutionMetadataView {
+            version: 3,
+            gas_profile,
+        };
+        outcome
+    }
+
+    #[test]
+    fn rewards_contract_share_of_wasm_gas() {
+        let gas_price = 100_000_000;
+        let outcome = outcome(
+            5_000_000_000_000,
+            5_000_000_000_000 * gas_price,
+            Some(vec![
+                cost("ACTION_COST", "FUNCTION_CALL_BASE", 1_000_000_000_000),
+                cost("WASM_HOST_COST", "CONTRACT_LOADING_BASE", 1_000_000_000_000),
+                cost("WASM_HOST_COST", "WASM_INSTRUCTION", 3_000_000_000_000),
+            ]),
+        );
+        assert_eq!(contract_reward(&outcome), 1_200_000_000_000 * gas_price);
+    }
+
+    #[test]
+    fn skips_outcomes_without_contract_execution() {
+        let transfer = outcome(
+            223_182_562_500,
+            22_318_256_250_000_000_000,
+            Some(vec![cost("ACTION_COST", "TRANSFER", 223_182_562_500)]),
+        );
+        assert_eq!(contract_reward(&transfer), 0);
+
+        let legacy = outcome(2_428_000_000_000, 242_800_000_000_000_000_000, None);
+        assert_eq!(contract_reward(&legacy), 0);
+    }
+
+    #[test]
+    fn skips_refunds() {
+        let refund = outcome(
+            223_182_562_500,
+            0,
+            Some(vec![cost("WASM_HOST_COST", "WASM_INSTRUCTION", 1_000)]),
+        );
+        assert_eq!(contract_reward(&refund), 0);
+    }
+}
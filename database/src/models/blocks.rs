@@ -3,7 +3,7 @@ use std::str::FromStr;
 use bigdecimal::BigDecimal;
 
 use crate::schema;
-use schema::blocks;
+use schema::{block_headers, blocks};
 
 #[derive(Insertable, Queryable, Clone, Debug)]
 pub struct Block {
@@ -31,3 +31,47 @@ impl From<&near_indexer_primitives::views::BlockView> for Block {
         }
     }
 }
+
+#[derive(Insertable, Queryable, Clone, Debug)]
+pub struct BlockHeader {
+    pub block_hash: String,
+    pub block_height: BigDecimal,
+    pub epoch_id: String,
+    pub next_epoch_id: String,
+    pub chunks_included: BigDecimal,
+    pub validator_proposals: serde_json::Value,
+    pub challenges_result: serde_json::Value,
+    pub latest_protocol_version: i32,
+    pub random_value: String,
+    pub block_ordinal: Option<BigDecimal>,
+    pub last_final_block_hash: String,
+    pub last_ds_final_block_hash: String,
+    pub approvals_count: i32,
+}
+
+impl From<&near_indexer_primitives::views::BlockView> for BlockHeader {
+    fn from(block_view: &near_indexer_primitives::views::BlockView) -> Self {
+        let header = &block_view.header;
+        Self {
+            block_hash: header.hash.to_string(),
+            block_height: header.height.into(),
+            epoch_id: header.epoch_id.to_string(),
+            next_epoch_id: header.next_epoch_id.to_string(),
+            chunks_included: header.chunks_included.into(),
+            validator_proposals: serde_json::to_value(&header.validator_proposals)
+                .expect("`validator_proposals` expected to be serializable"),
+            challenges_result: serde_json::to_value(&header.challenges_result)
+                .expect("`challenges_result` expected to be serializable"),
+            latest_protocol_version: header.latest_protocol_version as i32,
+            random_value: header.random_value.to_string(),
+            block_ordinal: header.block_ordinal.map(Into::into),
+            last_final_block_hash: header.last_final_block.to_string(),
+            last_ds_final_block_hash: header.last_ds_final_block.to_string(),
+            approvals_count: header
+                .approvals
+                .iter()
+                .filter(|approval| approval.is_some())
+                .count() as i32,
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use bigdecimal::BigDecimal;
 
 use crate::schema;
-use schema::chunks;
+use schema::{chunks, missing_chunks};
 
 #[derive(Insertable, Clone, Debug)]
 pub struct Chunk {
@@ -12,6 +12,15 @@ pub struct Chunk {
     pub gas_limit: BigDecimal,
     pub gas_used: BigDecimal,
     pub author_account_id: String,
+    pub prev_state_root: String,
+    pub outcome_root: String,
+    pub encoded_length: BigDecimal,
+    pub height_created: BigDecimal,
+    pub height_included: BigDecimal,
+    pub balance_burnt: BigDecimal,
+    pub outgoing_receipts_root: String,
+    pub tx_root: String,
+    pub validator_proposals: serde_json::Value,
 }
 
 impl Chunk {
@@ -27,6 +36,23 @@ impl Chunk {
             gas_limit: chunk_view.header.gas_limit.into(),
             gas_used: chunk_view.header.gas_used.into(),
             author_account_id: chunk_view.author.to_string(),
+            prev_state_root: chunk_view.header.prev_state_root.to_string(),
+            outcome_root: chunk_view.header.outcome_root.to_string(),
+            encoded_length: chunk_view.header.encoded_length.into(),
+            height_created: chunk_view.header.height_created.into(),
+            height_included: chunk_view.header.height_included.into(),
+            balance_burnt: crate::models::u128_to_big_decimal(chunk_view.header.balance_burnt),
+            outgoing_receipts_root: chunk_view.header.outgoing_receipts_root.to_string(),
+            tx_root: chunk_view.header.tx_root.to_string(),
+            validator_proposals: serde_json::to_value(&chunk_view.header.validator_proposals)
+                .expect("`validator_proposals` expected to be serializable"),
         }
     }
 }
+
+/// The shard which did not get the chunk in the block
+#[derive(Insertable, Queryable, Clone, Debug)]
+pub struct MissingChunk {
+    pub included_in_block_hash: String,
+    pub shard_id: BigDecimal,
+}
@@ -0,0 +1,62 @@
+use bigdecimal::BigDecimal;
+
+use crate::schema;
+use schema::{contract_codes, contract_deployments};
+
+#[derive(Insertable, Queryable, Clone, Debug)]
+pub struct ContractDeployment {
+    pub receipt_id: String,
+    pub index_in_action_receipt: i32,
+    pub contract_account_id: String,
+    pub code_hash: String,
+    pub code_size: BigDecimal,
+    pub deployed_in_block_height: BigDecimal,
+    pub deployed_in_block_timestamp: BigDecimal,
+}
+
+impl ContractDeployment {
+    pub fn from_deploy_contract_action(
+        receipt: &near_indexer_primitives::views::ReceiptView,
+        index_in_action_receipt: i32,
+        code: &[u8],
+        block_height: near_indexer_primitives::types::BlockHeight,
+        block_timestamp: u64,
+    ) -> Self {
+        Self {
+            receipt_id: receipt.receipt_id.to_string(),
+            index_in_action_receipt,
+            contract_account_id: receipt.receiver_id.to_string(),
+            code_hash: near_primitives::hash::hash(code).to_string(),
+            code_size: BigDecimal::from(code.len() as u64),
+            deployed_in_block_height: block_height.into(),
+            deployed_in_block_timestamp: block_timestamp.into(),
+        }
+    }
+}
+
+#[derive(Insertable, Queryable, Clone)]
+pub struct ContractCode {
+    pub code_hash: String,
+    pub code_size: BigDecimal,
+    pub code: Vec<u8>,
+}
+
+impl ContractCode {
+    pub fn from_code(code: &[u8]) -> Self {
+        Self {
+            code_hash: near_primitives::hash::hash(code).to_string(),
+            code_size: BigDecimal::from(code.len() as u64),
+            code: code.to_vec(),
+        }
+    }
+}
+
+/// The code itself is too big to be printed in the logs
+impl std::fmt::Debug for ContractCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContractCode")
+            .field("code_hash", &self.code_hash)
+            .field("code_size", &self.code_size)
+            .finish()
+    }
+}
@@ -18,7 +18,7 @@ impl From<&near_indexer_primitives::views::ReceiptEnumView> for ReceiptKind {
     }
 }
 
-#[derive(Debug, DbEnum, Clone)]
+#[derive(Debug, DbEnum, Clone, PartialEq, Eq, Hash)]
 #[DbValueStyle = "SCREAMING_SNAKE_CASE"]
 #[DieselType = "Action_kind"]
 #[PgType = "action_kind"]
@@ -60,7 +60,7 @@ impl From<near_indexer_primitives::views::ExecutionStatusView> for ExecutionOutc
     }
 }
 
-#[derive(Debug, DbEnum, Clone)]
+#[derive(Debug, DbEnum, Clone, PartialEq, Eq)]
 #[DbValueStyle = "SCREAMING_SNAKE_CASE"]
 #[DieselType = "Access_key_permission_kind"]
 #[PgType = "access_key_permission_kind"]
@@ -93,6 +93,35 @@ impl From<&near_primitives::account::AccessKeyPermission> for AccessKeyPermissio
     }
 }
 
+#[derive(Debug, DbEnum, Clone)]
+#[DbValueStyle = "SCREAMING_SNAKE_CASE"]
+#[DieselType = "Access_key_history_action"]
+#[PgType = "access_key_history_action"]
+pub enum AccessKeyHistoryAction {
+    Add,
+    Delete,
+    /// The existing key got the different permission in place, e.g. it was deleted and re-added by one receipt
+    PermissionChange,
+}
+
+#[derive(Debug, DbEnum, Clone, Copy, PartialEq, Eq)]
+#[DbValueStyle = "SCREAMING_SNAKE_CASE"]
+#[DieselType = "Account_kind"]
+#[PgType = "account_kind"]
+pub enum AccountKind {
+    Named,
+    NearImplicit,
+    EthImplicit,
+    SubAccount,
+}
+
+impl AccountKind {
+    /// Implicit accounts are created by the transfer to them, without `CreateAccount` action
+    pub fn is_implicit(&self) -> bool {
+        matches!(self, Self::NearImplicit | Self::EthImplicit)
+    }
+}
+
 #[derive(Debug, DbEnum, Clone)]
 #[DbValueStyle = "SCREAMING_SNAKE_CASE"]
 #[DieselType = "State_change_reason_kind"]
@@ -147,3 +176,40 @@ pub enum FtEventKind {
     Transfer,
     Burn,
 }
+
+#[derive(Debug, DbEnum, Clone)]
+#[DbValueStyle = "SCREAMING_SNAKE_CASE"]
+#[DieselType = "Mt_event_kind"]
+#[PgType = "mt_event_kind"]
+pub enum MtEventKind {
+    Mint,
+    Transfer,
+    Burn,
+}
+
+#[derive(Debug, DbEnum, Clone)]
+#[DbValueStyle = "SCREAMING_SNAKE_CASE"]
+#[DieselType = "Event_invalidity_reason"]
+#[PgType = "event_invalidity_reason"]
+pub enum EventInvalidityReason {
+    /// Amount is not a decimal u128 number
+    InvalidAmount,
+    /// One of the account ids mentioned in the event is not a valid NEAR account id
+    InvalidAccountId,
+    /// Transfer is initiated neither by the old owner, nor by the authorized account,
+    /// nor by the contract itself
+    TransferNotByOwner,
+    /// NEP-245 `token_ids` and `amounts` arrays have different lengths
+    TokenIdsAndAmountsMismatch,
+}
+
+#[derive(Debug, DbEnum, Clone, Copy, PartialEq, Eq)]
+#[DbValueStyle = "SCREAMING_SNAKE_CASE"]
+#[DieselType = "Staking_event_kind"]
+#[PgType = "staking_event_kind"]
+pub enum StakingEventKind {
+    Deposit,
+    Stake,
+    Unstake,
+    Withdraw,
+}
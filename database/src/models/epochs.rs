@@ -0,0 +1,92 @@
+use bigdecimal::BigDecimal;
+
+use crate::schema;
+use schema::{epoch_validators, epochs};
+
+#[derive(Insertable, Queryable, Clone, Debug)]
+pub struct Epoch {
+    pub epoch_id: String,
+    pub epoch_height: Option<BigDecimal>,
+    pub start_block_height: Option<BigDecimal>,
+    pub start_block_hash: Option<String>,
+    pub start_block_timestamp: Option<BigDecimal>,
+    pub end_block_height: Option<BigDecimal>,
+    pub protocol_version: Option<i32>,
+}
+
+impl Epoch {
+    /// The epoch started with the given block
+    pub fn from_start_block_header(
+        block_header: &near_indexer_primitives::views::BlockHeaderView,
+        epoch_height: Option<near_indexer_primitives::types::EpochHeight>,
+        protocol_version: Option<near_indexer_primitives::types::ProtocolVersion>,
+    ) -> Self {
+        Self {
+            epoch_id: block_header.epoch_id.to_string(),
+            epoch_height: epoch_height.map(Into::into),
+            start_block_height: Some(block_header.height.into()),
+            start_block_hash: Some(block_header.hash.to_string()),
+            start_block_timestamp: Some(block_header.timestamp.into()),
+            end_block_height: None,
+            protocol_version: protocol_version.map(|protocol_version| protocol_version as i32),
+        }
+    }
+
+    /// The epoch finished right before the given block.
+    /// We know its start only if we have the validators info
+    pub fn from_next_epoch_start_block_header(
+        epoch_id: &near_indexer_primitives::CryptoHash,
+        next_epoch_start_block_header: &near_indexer_primitives::views::BlockHeaderView,
+        epoch_validators_info: Option<&near_indexer_primitives::views::EpochValidatorInfo>,
+    ) -> Self {
+        Self {
+            epoch_id: epoch_id.to_string(),
+            epoch_height: epoch_validators_info
+                .map(|epoch_validators_info| epoch_validators_info.epoch_height.into()),
+            start_block_height: epoch_validators_info
+                .map(|epoch_validators_info| epoch_validators_info.epoch_start_height.into()),
+            start_block_hash: None,
+            start_block_timestamp: None,
+            end_block_height: next_epoch_start_block_header.prev_height.map(Into::into),
+            protocol_version: None,
+        }
+    }
+}
+
+#[derive(Insertable, Queryable, Clone, Debug)]
+pub struct EpochValidator {
+    pub epoch_id: String,
+    pub account_id: String,
+    pub public_key: String,
+    pub stake: BigDecimal,
+    pub is_slashed: bool,
+    pub shards: Vec<BigDecimal>,
+    pub num_produced_blocks: BigDecimal,
+    pub num_expected_blocks: BigDecimal,
+    pub num_produced_chunks: BigDecimal,
+    pub num_expected_chunks: BigDecimal,
+}
+
+impl EpochValidator {
+    pub fn from_validator_info(
+        epoch_id: &near_indexer_primitives::CryptoHash,
+        validator_info: &near_indexer_primitives::views::CurrentEpochValidatorInfo,
+    ) -> Self {
+        Self {
+            epoch_id: epoch_id.to_string(),
+            account_id: validator_info.account_id.to_string(),
+            public_key: validator_info.public_key.to_string(),
+            stake: crate::models::u128_to_big_decimal(validator_info.stake),
+            is_slashed: validator_info.is_slashed,
+            shards: validator_info
+                .shards
+                .iter()
+                .map(|shard_id| (*shard_id).into())
+                .collect(),
+            num_produced_blocks: validator_info.num_produced_blocks.into(),
+            num_expected_blocks: validator_info.num_expected_blocks.into(),
+            num_produced_chunks: validator_info.num_produced_chunks.into(),
+            num_expected_chunks: validator_info.num_expected_chunks.into(),
+        }
+    }
+}
@@ -5,7 +5,10 @@ use bigdecimal::BigDecimal;
 use crate::models::enums::ExecutionOutcomeStatus;
 
 use crate::schema;
-use schema::{execution_outcome_receipts, execution_outcomes};
+use schema::{
+    execution_outcome_gas_profiles, execution_outcome_proofs, execution_outcome_receipts,
+    execution_outcomes,
+};
 
 #[derive(Insertable, Clone, Debug)]
 pub struct ExecutionOutcome {
@@ -18,6 +21,10 @@ pub struct ExecutionOutcome {
     pub executor_account_id: String,
     pub status: ExecutionOutcomeStatus,
     pub shard_id: BigDecimal,
+    pub logs: Option<Vec<String>>,
+    pub return_value_base64: Option<String>,
+    pub return_value_json: Option<serde_json::Value>,
+    pub failure: Option<serde_json::Value>,
 }
 
 impl ExecutionOutcome {
@@ -27,6 +34,30 @@ impl ExecutionOutcome {
         executed_in_block_timestamp: u64,
         shard_id: u64,
     ) -> Self {
+        let (return_value_base64, return_value_json, failure) =
+            match &execution_outcome.outcome.status {
+                near_indexer_primitives::views::ExecutionStatusView::SuccessValue(value) => {
+                    // Same as for args_json, we try if the value is a JSON
+                    let return_value_json = serde_json::from_slice::<serde_json::Value>(value)
+                        .ok()
+                        .map(|mut return_value_json| {
+                            crate::models::escape_json(&mut return_value_json);
+                            return_value_json
+                        });
+                    (Some(base64::encode(value)), return_value_json, None)
+                }
+                near_indexer_primitives::views::ExecutionStatusView::Failure(error) => {
+                    let mut failure = serde_json::to_value(error)
+                        .expect("TxExecutionError is expected to be serializable");
+                    crate::models::escape_json(&mut failure);
+                    (None, None, Some(failure))
+                }
+                near_indexer_primitives::views::ExecutionStatusView::Unknown
+                | near_indexer_primitives::views::ExecutionStatusView::SuccessReceiptId(_) => {
+                    (None, None, None)
+                }
+            };
+
         Self {
             executed_in_block_hash: execution_outcome.block_hash.to_string(),
             executed_in_block_timestamp: executed_in_block_timestamp.into(),
@@ -40,6 +71,18 @@ impl ExecutionOutcome {
             executor_account_id: execution_outcome.outcome.executor_id.to_string(),
             status: execution_outcome.outcome.status.clone().into(),
             shard_id: shard_id.into(),
+            // Postgres can't store null bytes in text, valid event JSON can't contain them anyway
+            logs: Some(
+                execution_outcome
+                    .outcome
+                    .logs
+                    .iter()
+                    .map(|log| log.replace('\0', "\\0"))
+                    .collect(),
+            ),
+            return_value_base64,
+            return_value_json,
+            failure,
         }
     }
 }
@@ -50,3 +93,56 @@ pub struct ExecutionOutcomeReceipt {
     pub index_in_execution_outcome: i32,
     pub produced_receipt_id: String,
 }
+
+/// One line of the gas profile, i.e. gas used for the specific cost during the receipt execution
+#[derive(Insertable, Queryable, Clone, Debug)]
+pub struct ExecutionOutcomeGasProfile {
+    pub receipt_id: String,
+    pub index_in_gas_profile: i32,
+    pub metadata_version: i32,
+    pub cost_category: String,
+    pub cost: String,
+    pub gas_used: BigDecimal,
+}
+
+impl ExecutionOutcomeGasProfile {
+    pub fn from_execution_outcome(
+        execution_outcome: &near_indexer_primitives::views::ExecutionOutcomeWithIdView,
+    ) -> Vec<Self> {
+        let metadata = &execution_outcome.outcome.metadata;
+        metadata
+            .gas_profile
+            .iter()
+            .flatten()
+            .enumerate()
+            .map(|(index, cost_gas_used)| Self {
+                receipt_id: execution_outcome.id.to_string(),
+                index_in_gas_profile: index as i32,
+                metadata_version: metadata.version as i32,
+                cost_category: cost_gas_used.cost_category.clone(),
+                cost: cost_gas_used.cost.clone(),
+                gas_used: cost_gas_used.gas_used.into(),
+            })
+            .collect()
+    }
+}
+
+#[derive(Insertable, Queryable, Clone, Debug)]
+pub struct ExecutionOutcomeProof {
+    pub receipt_id: String,
+    pub executed_in_block_hash: String,
+    pub proof: serde_json::Value,
+}
+
+impl ExecutionOutcomeProof {
+    pub fn from_execution_outcome(
+        execution_outcome: &near_indexer_primitives::views::ExecutionOutcomeWithIdView,
+    ) -> Self {
+        Self {
+            receipt_id: execution_outcome.id.to_string(),
+            executed_in_block_hash: execution_outcome.block_hash.to_string(),
+            proof: serde_json::to_value(&execution_outcome.proof)
+                .expect("MerklePath is expected to be serializable"),
+        }
+    }
+}
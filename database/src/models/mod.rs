@@ -1,3 +1,6 @@
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
 use diesel::PgConnection;
 
 pub use access_keys::AccessKey;
@@ -12,19 +15,24 @@ pub use receipts::{
 };
 pub use transactions::{Transaction, TransactionAction};
 
-pub(crate) use serializers::extract_action_type_and_value_from_action_view;
+pub(crate) use serializers::{escape_json, extract_action_type_and_value_from_action_view};
 
 pub mod access_keys;
+pub mod account_balance_changes;
 pub mod account_changes;
 pub mod accounts;
 pub mod aggregated;
 pub mod assets;
+pub mod block_fees;
 pub mod blocks;
 pub mod chunks;
+pub mod contract_deployments;
 pub mod enums;
+pub mod epochs;
 pub mod execution_outcomes;
 pub mod receipts;
 mod serializers;
+pub mod staking;
 pub mod transactions;
 
 pub fn establish_connection(database_url: &str) -> actix_diesel::Database<PgConnection> {
@@ -32,3 +40,8 @@ pub fn establish_connection(database_url: &str) -> actix_diesel::Database<PgConn
         .pool_max_size(30)
         .open(database_url)
 }
+
+/// Converts the token amounts (and other u128 values) to the `numeric` columns representation
+pub(crate) fn u128_to_big_decimal(value: u128) -> BigDecimal {
+    BigDecimal::from_str(value.to_string().as_str()).expect("u128 is expected to fit BigDecimal")
+}
@@ -0,0 +1,29 @@
+use bigdecimal::BigDecimal;
+
+use crate::models::enums::StakingEventKind;
+use crate::schema;
+use schema::{staking_events, staking_positions};
+
+#[derive(Insertable, Queryable, Clone, Debug)]
+pub struct StakingEvent {
+    pub receipt_id: String,
+    pub index_in_receipt: i32,
+    pub executed_in_block_timestamp: BigDecimal,
+    pub executed_in_block_height: BigDecimal,
+    pub pool_account_id: String,
+    pub delegator_account_id: String,
+    pub method_name: String,
+    pub event_kind: StakingEventKind,
+    pub amount: BigDecimal,
+    pub unstaked_balance_after_event: BigDecimal,
+    pub staking_shares_after_event: Option<BigDecimal>,
+}
+
+#[derive(Insertable, Queryable, Clone, Debug)]
+pub struct StakingPosition {
+    pub delegator_account_id: String,
+    pub pool_account_id: String,
+    pub unstaked_balance: BigDecimal,
+    pub staking_shares: Option<BigDecimal>,
+    pub last_update_block_height: BigDecimal,
+}
@@ -13,6 +13,25 @@
 // - git diff -U6 src/schema.rs > src/schema.patch
 // - Uncomment patch line
 
+table! {
+    use diesel::sql_types::*;
+    use crate::models::enums::*;
+
+    access_key_history (caused_by_receipt_id, index_in_receipt) {
+        public_key -> Text,
+        account_id -> Text,
+        caused_by_receipt_id -> Text,
+        index_in_receipt -> Int4,
+        changed_in_block_height -> Numeric,
+        action -> Access_key_history_action,
+        permission_kind -> Nullable<Access_key_permission_kind>,
+        permission_allowance -> Nullable<Numeric>,
+        permission_receiver_id -> Nullable<Text>,
+        permission_method_names -> Nullable<Array<Text>>,
+        nonce -> Nullable<Numeric>,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use crate::models::enums::*;
@@ -24,6 +43,28 @@ table! {
         deleted_by_receipt_id -> Nullable<Text>,
         permission_kind -> Access_key_permission_kind,
         last_update_block_height -> Numeric,
+        permission_allowance -> Nullable<Numeric>,
+        permission_receiver_id -> Nullable<Text>,
+        permission_method_names -> Nullable<Array<Text>>,
+        nonce -> Nullable<Numeric>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::models::enums::*;
+
+    account_balance_changes (changed_in_block_height, index_in_block) {
+        account_id -> Text,
+        changed_in_block_height -> Numeric,
+        changed_in_block_timestamp -> Numeric,
+        index_in_block -> Int4,
+        caused_by_receipt_id -> Nullable<Text>,
+        caused_by_transaction_hash -> Nullable<Text>,
+        update_reason -> State_change_reason_kind,
+        nonstaked_balance -> Numeric,
+        staked_balance -> Numeric,
+        storage_usage -> Numeric,
     }
 }
 
@@ -49,12 +90,42 @@ table! {
 table! {
     use diesel::sql_types::*;
 
+    account_lifecycles (id) {
+        id -> Int8,
+        account_id -> Text,
+        created_by_receipt_id -> Nullable<Text>,
+        created_in_block_height -> Nullable<Numeric>,
+        deleted_by_receipt_id -> Nullable<Text>,
+        deleted_in_block_height -> Nullable<Numeric>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+
+    account_state (account_id) {
+        account_id -> Text,
+        nonstaked_balance -> Numeric,
+        staked_balance -> Numeric,
+        storage_usage -> Numeric,
+        code_hash -> Text,
+        is_deleted -> Bool,
+        last_update_block_height -> Numeric,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::models::enums::*;
+
     accounts (id) {
         id -> Int8,
         account_id -> Text,
         created_by_receipt_id -> Nullable<Text>,
         deleted_by_receipt_id -> Nullable<Text>,
         last_update_block_height -> Numeric,
+        account_kind -> Account_kind,
+        parent_account_id -> Nullable<Text>,
     }
 }
 
@@ -106,6 +177,22 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use crate::models::enums::*;
+
+    #[allow(non_snake_case)]
+    aggregated__account_interactions (predecessor_account_id, receiver_account_id, day_timestamp, action_kind) {
+        predecessor_account_id -> Text,
+        receiver_account_id -> Text,
+        day_timestamp -> Numeric,
+        action_kind -> Action_kind,
+        actions_count -> Int8,
+        total_deposit -> Numeric,
+        total_gas_burnt -> Numeric,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
 
@@ -122,6 +209,77 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+
+    #[allow(non_snake_case)]
+    aggregated__contract_usage (contract_account_id, day_timestamp, method_name) {
+        contract_account_id -> Text,
+        day_timestamp -> Numeric,
+        method_name -> Text,
+        calls_count -> Int8,
+        unique_callers_count -> Int8,
+        total_gas_burnt -> Numeric,
+        total_deposit -> Numeric,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+
+    #[allow(non_snake_case)]
+    aggregated__contract_usage_callers (contract_account_id, day_timestamp, method_name, caller_account_id) {
+        contract_account_id -> Text,
+        day_timestamp -> Numeric,
+        method_name -> Text,
+        caller_account_id -> Text,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+
+    #[allow(non_snake_case)]
+    aggregated__daily_stats (day_timestamp) {
+        day_timestamp -> Numeric,
+        transactions_count -> Int8,
+        active_accounts_count -> Int8,
+        new_accounts_count -> Int8,
+        deleted_accounts_count -> Int8,
+        gas_used -> Numeric,
+        tokens_burnt -> Numeric,
+        deployed_contracts_count -> Int8,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+
+    #[allow(non_snake_case)]
+    aggregated__epoch_supply (epoch_id) {
+        epoch_id -> Text,
+        epoch_height -> Nullable<Numeric>,
+        start_block_height -> Numeric,
+        start_block_timestamp -> Numeric,
+        end_block_height -> Numeric,
+        end_block_timestamp -> Numeric,
+        total_supply_before -> Numeric,
+        total_supply_after -> Numeric,
+        minted_tokens -> Numeric,
+        burnt_tokens -> Numeric,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+
+    #[allow(non_snake_case)]
+    aggregated__processed_blocks (aggregation, block_height) {
+        aggregation -> Text,
+        block_height -> Numeric,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use crate::models::enums::*;
@@ -138,6 +296,46 @@ table! {
         token_old_owner_account_id -> Text,
         token_new_owner_account_id -> Text,
         event_memo -> Text,
+        derived_from_function_call -> Bool,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::models::enums::*;
+
+    #[allow(non_snake_case)]
+    assets__invalid_events (emitted_for_receipt_id, event_standard, emitted_index_of_event_entry_in_shard) {
+        emitted_for_receipt_id -> Text,
+        emitted_at_block_timestamp -> Numeric,
+        emitted_in_shard_id -> Numeric,
+        emitted_index_of_event_entry_in_shard -> Int4,
+        emitted_by_contract_account_id -> Text,
+        event_standard -> Text,
+        event_kind -> Text,
+        event_data -> Jsonb,
+        invalidity_reason -> Event_invalidity_reason,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::models::enums::*;
+
+    #[allow(non_snake_case)]
+    assets__multi_token_events (emitted_for_receipt_id, emitted_index_of_event_entry_in_shard) {
+        emitted_for_receipt_id -> Text,
+        emitted_at_block_timestamp -> Numeric,
+        emitted_in_shard_id -> Numeric,
+        emitted_index_of_event_entry_in_shard -> Int4,
+        emitted_by_contract_account_id -> Text,
+        token_id -> Text,
+        amount -> Text,
+        event_kind -> Mt_event_kind,
+        token_old_owner_account_id -> Text,
+        token_new_owner_account_id -> Text,
+        token_authorized_account_id -> Text,
+        event_memo -> Text,
     }
 }
 
@@ -171,6 +369,42 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+
+    block_fees (block_height) {
+        block_height -> Numeric,
+        block_hash -> Text,
+        block_timestamp -> Numeric,
+        gas_price -> Numeric,
+        gas_used -> Numeric,
+        transactions_tokens_burnt -> Numeric,
+        receipts_tokens_burnt -> Numeric,
+        tokens_burnt -> Numeric,
+        contract_reward -> Numeric,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+
+    block_headers (block_hash) {
+        block_hash -> Text,
+        block_height -> Numeric,
+        epoch_id -> Text,
+        next_epoch_id -> Text,
+        chunks_included -> Numeric,
+        validator_proposals -> Jsonb,
+        challenges_result -> Jsonb,
+        latest_protocol_version -> Int4,
+        random_value -> Text,
+        block_ordinal -> Nullable<Numeric>,
+        last_final_block_hash -> Text,
+        last_ds_final_block_hash -> Text,
+        approvals_count -> Int4,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
 
@@ -196,6 +430,39 @@ table! {
         gas_limit -> Numeric,
         gas_used -> Numeric,
         author_account_id -> Text,
+        prev_state_root -> Nullable<Text>,
+        outcome_root -> Nullable<Text>,
+        encoded_length -> Nullable<Numeric>,
+        height_created -> Nullable<Numeric>,
+        height_included -> Nullable<Numeric>,
+        balance_burnt -> Nullable<Numeric>,
+        outgoing_receipts_root -> Nullable<Text>,
+        tx_root -> Nullable<Text>,
+        validator_proposals -> Nullable<Jsonb>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+
+    contract_codes (code_hash) {
+        code_hash -> Text,
+        code_size -> Numeric,
+        code -> Bytea,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+
+    contract_deployments (receipt_id, index_in_action_receipt) {
+        receipt_id -> Text,
+        index_in_action_receipt -> Int4,
+        contract_account_id -> Text,
+        code_hash -> Text,
+        code_size -> Numeric,
+        deployed_in_block_height -> Numeric,
+        deployed_in_block_timestamp -> Numeric,
     }
 }
 
@@ -209,6 +476,60 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+
+    epoch_validators (epoch_id, account_id) {
+        epoch_id -> Text,
+        account_id -> Text,
+        public_key -> Text,
+        stake -> Numeric,
+        is_slashed -> Bool,
+        shards -> Array<Numeric>,
+        num_produced_blocks -> Numeric,
+        num_expected_blocks -> Numeric,
+        num_produced_chunks -> Numeric,
+        num_expected_chunks -> Numeric,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+
+    epochs (epoch_id) {
+        epoch_id -> Text,
+        epoch_height -> Nullable<Numeric>,
+        start_block_height -> Nullable<Numeric>,
+        start_block_hash -> Nullable<Text>,
+        start_block_timestamp -> Nullable<Numeric>,
+        end_block_height -> Nullable<Numeric>,
+        protocol_version -> Nullable<Int4>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+
+    execution_outcome_gas_profiles (receipt_id, index_in_gas_profile) {
+        receipt_id -> Text,
+        index_in_gas_profile -> Int4,
+        metadata_version -> Int4,
+        cost_category -> Text,
+        cost -> Text,
+        gas_used -> Numeric,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+
+    execution_outcome_proofs (receipt_id) {
+        receipt_id -> Text,
+        executed_in_block_hash -> Text,
+        proof -> Jsonb,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
 
@@ -233,6 +554,19 @@ table! {
         executor_account_id -> Text,
         status -> Execution_outcome_status,
         shard_id -> Numeric,
+        logs -> Nullable<Array<Text>>,
+        return_value_base64 -> Nullable<Text>,
+        return_value_json -> Nullable<Jsonb>,
+        failure -> Nullable<Jsonb>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+
+    missing_chunks (included_in_block_hash, shard_id) {
+        included_in_block_hash -> Text,
+        shard_id -> Numeric,
     }
 }
 
@@ -253,6 +587,37 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use crate::models::enums::*;
+
+    staking_events (receipt_id, index_in_receipt) {
+        receipt_id -> Text,
+        index_in_receipt -> Int4,
+        executed_in_block_timestamp -> Numeric,
+        executed_in_block_height -> Numeric,
+        pool_account_id -> Text,
+        delegator_account_id -> Text,
+        method_name -> Text,
+        event_kind -> Staking_event_kind,
+        amount -> Numeric,
+        unstaked_balance_after_event -> Numeric,
+        staking_shares_after_event -> Nullable<Numeric>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+
+    staking_positions (delegator_account_id, pool_account_id) {
+        delegator_account_id -> Text,
+        pool_account_id -> Text,
+        unstaked_balance -> Numeric,
+        staking_shares -> Nullable<Numeric>,
+        last_update_block_height -> Numeric,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use crate::models::enums::*;
@@ -290,37 +655,71 @@ table! {
     }
 }
 
+joinable!(access_key_history -> receipts (caused_by_receipt_id));
 joinable!(action_receipt_actions -> receipts (receipt_id));
 joinable!(aggregated__circulating_supply -> blocks (computed_at_block_hash));
+joinable!(assets__invalid_events -> receipts (emitted_for_receipt_id));
+joinable!(assets__multi_token_events -> receipts (emitted_for_receipt_id));
 joinable!(assets__non_fungible_token_events -> receipts (emitted_for_receipt_id));
+joinable!(block_fees -> blocks (block_hash));
+joinable!(block_headers -> blocks (block_hash));
 joinable!(chunks -> blocks (included_in_block_hash));
+joinable!(contract_deployments -> receipts (receipt_id));
+joinable!(epoch_validators -> epochs (epoch_id));
+joinable!(execution_outcome_gas_profiles -> execution_outcomes (receipt_id));
+joinable!(execution_outcome_proofs -> execution_outcomes (receipt_id));
 joinable!(execution_outcome_receipts -> execution_outcomes (executed_receipt_id));
 joinable!(execution_outcome_receipts -> receipts (executed_receipt_id));
 joinable!(execution_outcomes -> blocks (executed_in_block_hash));
 joinable!(execution_outcomes -> receipts (receipt_id));
+joinable!(missing_chunks -> blocks (included_in_block_hash));
 joinable!(receipts -> blocks (included_in_block_hash));
 joinable!(receipts -> chunks (included_in_chunk_hash));
 joinable!(receipts -> transactions (originated_from_transaction_hash));
+joinable!(staking_events -> execution_outcomes (receipt_id));
 joinable!(transaction_actions -> transactions (transaction_hash));
 joinable!(transactions -> blocks (included_in_block_hash));
 joinable!(transactions -> chunks (included_in_chunk_hash));
 
 allow_tables_to_appear_in_same_query!(
+    access_key_history,
     access_keys,
+    account_balance_changes,
+    account_lifecycles,
+    account_state,
     accounts,
     action_receipt_actions,
     action_receipt_input_data,
     action_receipt_output_data,
     action_receipts,
+    aggregated__account_interactions,
     aggregated__circulating_supply,
+    aggregated__contract_usage,
+    aggregated__contract_usage_callers,
+    aggregated__daily_stats,
+    aggregated__epoch_supply,
+    aggregated__processed_blocks,
     assets__fungible_token_events,
+    assets__invalid_events,
+    assets__multi_token_events,
     assets__non_fungible_token_events,
+    block_fees,
+    block_headers,
     blocks,
     chunks,
+    contract_codes,
+    contract_deployments,
     data_receipts,
+    epoch_validators,
+    epochs,
+    execution_outcome_gas_profiles,
+    execution_outcome_proofs,
     execution_outcome_receipts,
     execution_outcomes,
+    missing_chunks,
     receipts,
+    staking_events,
+    staking_positions,
     transaction_actions,
     transactions,
 );
@@ -27,12 +27,53 @@ pub(crate) struct Opts {
     /// Enabled Indexer for Explorer debug level of logs
     #[clap(long)]
     pub debug: bool,
-    /// Switches indexer to non-strict mode (skips Receipts without parent Transaction hash, stops storing AccountChanges and AccessKeys)
+    /// Switches indexer to non-strict mode (skips Receipts without parent Transaction hash, stops storing AccountChanges, AccountBalanceChanges and AccessKeys)
     #[clap(long)]
     pub non_strict_mode: bool,
     /// Sets the concurrency for indexing. Note: concurrency (set to 2+) may lead to warnings due to tight constraints between transactions and receipts (those will get resolved eventually, but unless it is the second pass of indexing, concurrency won't help at the moment).
     #[clap(long, default_value = "1")]
     pub concurrency: std::num::NonZeroU16,
+    /// Path to JSON file with allowlist/denylist of contracts whose events (NFT, FT, MT) are stored.
+    /// The file is watched and the rules are reloaded on change without the restart
+    #[clap(long, env)]
+    pub assets_filter_file: Option<std::path::PathBuf>,
+    /// Validates NEP events payloads (amounts, account ids, transfer initiators) and stores malformed event entries to the separate table instead of the assets tables
+    #[clap(long)]
+    pub strict_events_validation: bool,
+    /// Restores FT transfers from `ft_transfer`, `ft_transfer_call` and `ft_resolve_transfer` calls of the contracts which do not emit NEP-141 events.
+    /// Only the contracts from `legacy_ft_contracts` list of `--assets-filter-file` are considered
+    #[cfg(feature = "fungible_token_events")]
+    #[clap(long)]
+    pub detect_legacy_ft_transfers: bool,
+    /// Stores the gas profile of every execution outcome (gas used per cost) to `execution_outcome_gas_profiles` table
+    #[clap(long)]
+    pub store_gas_profiles: bool,
+    /// Stores the Merkle proofs of execution outcomes with their block hashes to `execution_outcome_proofs` table (for light client verification)
+    #[clap(long)]
+    pub store_outcome_proofs: bool,
+    /// Requests the validators info from RPC at each epoch change and stores it to `epoch_validators` table
+    #[clap(long)]
+    pub store_epoch_validators: bool,
+    /// JSON RPC URL to use instead of the public one (e.g. the local node)
+    #[clap(long = "rpc-url", env = "RPC_URL")]
+    pub custom_rpc_url: Option<String>,
+    /// Stores the code of the deployed contracts to `contract_codes` table (each code once)
+    #[clap(long)]
+    pub store_contract_code: bool,
+    /// Adds the actions of each block to `aggregated__account_interactions` table (who interacts with whom per day)
+    #[clap(long)]
+    pub aggregate_account_interactions: bool,
+    /// Adds the function calls of each block to `aggregated__contract_usage` table (calls, unique callers, gas and deposit per contract method per day)
+    #[clap(long)]
+    pub aggregate_contract_usage: bool,
+    /// Comma-separated staking pool accounts (`*.poolv1.near` matches all the pools of the factory) whose delegator deposits, stakes, unstakes and withdrawals are stored
+    #[clap(
+        long,
+        env,
+        use_value_delimiter = true,
+        default_value = "*.poolv1.near,*.pool.near"
+    )]
+    pub staking_pools: Vec<adapters::assets::filter::ContractPattern>,
     /// Port to enable metrics/health service
     #[clap(long, short, env, default_value_t = 3030)]
     pub port: u16,
@@ -62,6 +103,30 @@ pub enum StartOptions {
     FromLatest,
     /// Store genesis data and start from first block
     FromGenesis,
+    /// Extract NEP events from the execution outcome logs already stored in the database
+    /// and fill the assets tables for the given range of blocks (inclusive). NEAR Lake is not used
+    BackfillEvents {
+        #[clap(long)]
+        from: u64,
+        #[clap(long)]
+        to: u64,
+    },
+    /// Recompute `aggregated__account_interactions` for the given UTC days (inclusive, YYYY-MM-DD)
+    /// from the indexed tables. NEAR Lake is not used
+    RebuildAccountInteractions {
+        #[clap(long)]
+        from: chrono::NaiveDate,
+        #[clap(long)]
+        to: chrono::NaiveDate,
+    },
+    /// Recompute `aggregated__contract_usage` for the given UTC days (inclusive, YYYY-MM-DD)
+    /// from the indexed tables. NEAR Lake is not used
+    RebuildContractUsage {
+        #[clap(long)]
+        from: chrono::NaiveDate,
+        #[clap(long)]
+        to: chrono::NaiveDate,
+    },
 }
 
 impl Opts {
@@ -75,6 +140,9 @@ impl Opts {
     }
 
     pub fn rpc_url(&self) -> &str {
+        if let Some(rpc_url) = &self.custom_rpc_url {
+            return rpc_url;
+        }
         match self.chain_id {
             ChainId::Mainnet(_) => "https://rpc.mainnet.near.org",
             ChainId::Testnet(_) => "https://rpc.testnet.near.org",
@@ -129,6 +197,17 @@ async fn get_start_block_height(opts: &Opts) -> u64 {
         // Since NEAR Lake stores blocks in ascending order, using 0 here forces
         // near-lake-framework to start from the first block, i.e. genesis
         StartOptions::FromGenesis => 0,
+        StartOptions::BackfillEvents { .. } => {
+            unreachable!("Backfilling events does not stream the blocks from NEAR Lake")
+        }
+        StartOptions::RebuildAccountInteractions { .. } => {
+            unreachable!(
+                "Rebuilding account interactions does not stream the blocks from NEAR Lake"
+            )
+        }
+        StartOptions::RebuildContractUsage { .. } => {
+            unreachable!("Rebuilding contract usage does not stream the blocks from NEAR Lake")
+        }
     }
 }
 
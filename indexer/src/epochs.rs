@@ -0,0 +1,56 @@
+use near_jsonrpc_client::{methods, JsonRpcClient};
+use near_lake_framework::near_indexer_primitives::types::{
+    BlockId, BlockReference, EpochReference, ProtocolVersion,
+};
+use near_lake_framework::near_indexer_primitives::views;
+
+/// Epoch related data which is not present in the block header
+pub(crate) struct EpochStartInfo {
+    /// Protocol version of the started epoch
+    pub protocol_version: ProtocolVersion,
+    /// Validators info of the finished epoch
+    pub previous_epoch_validators_info: views::EpochValidatorInfo,
+}
+
+/// Fetches the data for the epoch started with the given block.
+/// Validators info is requested by the last block of the previous epoch,
+/// RPC provides it only for the finished epochs in such case
+pub(crate) async fn fetch_epoch_start_info(
+    rpc_url: &str,
+    block_header: &views::BlockHeaderView,
+) -> anyhow::Result<EpochStartInfo> {
+    let client = JsonRpcClient::connect(rpc_url);
+
+    let previous_epoch_validators_info = client
+        .call(methods::validators::RpcValidatorRequest {
+            epoch_reference: EpochReference::BlockId(BlockId::Hash(block_header.prev_hash)),
+        })
+        .await
+        .map_err(|err| {
+            anyhow::anyhow!(
+                "Failed to fetch validators for block {}: {:?}",
+                block_header.prev_hash,
+                err
+            )
+        })?;
+
+    let protocol_config = client
+        .call(
+            methods::EXPERIMENTAL_protocol_config::RpcProtocolConfigRequest {
+                block_reference: BlockReference::BlockId(BlockId::Hash(block_header.hash)),
+            },
+        )
+        .await
+        .map_err(|err| {
+            anyhow::anyhow!(
+                "Failed to fetch protocol config for block {}: {:?}",
+                block_header.hash,
+                err
+            )
+        })?;
+
+    Ok(EpochStartInfo {
+        protocol_version: protocol_config.protocol_version,
+        previous_epoch_validators_info,
+    })
+}
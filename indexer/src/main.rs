@@ -13,6 +13,7 @@ use explorer_database::{adapters, models, receipts_cache};
 use crate::configs::{Opts, StartOptions};
 
 mod configs;
+mod epochs;
 mod metrics;
 
 // Categories for logging
@@ -26,6 +27,9 @@ async fn handle_message(
     streamer_message: near_lake_framework::near_indexer_primitives::StreamerMessage,
     strict_mode: bool,
     receipts_cache_arc: receipts_cache::ReceiptsCacheArc,
+    assets_filter_arc: adapters::assets::filter::ContractsFilterArc,
+    opts: &configs::Opts,
+    previous_epoch_id: Option<near_lake_framework::near_indexer_primitives::CryptoHash>,
 ) -> anyhow::Result<()> {
     metrics::BLOCK_COUNT.inc();
     metrics::LATEST_BLOCK_HEIGHT.set(streamer_message.block.header.height.try_into().unwrap());
@@ -36,6 +40,40 @@ async fn handle_message(
     );
     adapters::blocks::store_block(pool, &streamer_message.block).await?;
 
+    // Epochs
+    if let Some(previous_epoch_id) = previous_epoch_id {
+        let epoch_start_info = if opts.store_epoch_validators {
+            match epochs::fetch_epoch_start_info(opts.rpc_url(), &streamer_message.block.header)
+                .await
+            {
+                Ok(epoch_start_info) => Some(epoch_start_info),
+                Err(err) => {
+                    tracing::warn!(
+                        target: INDEXER_FOR_EXPLORER,
+                        "Epoch {} is stored without validators: {:#}",
+                        streamer_message.block.header.epoch_id,
+                        err,
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        adapters::epochs::store_epoch_start(
+            pool,
+            &streamer_message.block.header,
+            &previous_epoch_id,
+            epoch_start_info
+                .as_ref()
+                .map(|epoch_start_info| epoch_start_info.protocol_version),
+            epoch_start_info
+                .as_ref()
+                .map(|epoch_start_info| &epoch_start_info.previous_epoch_validators_info),
+        )
+        .await?;
+    }
+
     // Chunks
     adapters::chunks::store_chunks(
         pool,
@@ -72,12 +110,37 @@ async fn handle_message(
     try_join!(transactions_future, receipts_future)?;
 
     // ExecutionOutcomes
-    let execution_outcomes_future = adapters::execution_outcomes::store_execution_outcomes(
-        pool,
-        &streamer_message.shards,
-        streamer_message.block.header.timestamp,
-        receipts_cache_arc.clone(),
-    );
+    let execution_outcomes_future = async {
+        adapters::execution_outcomes::store_execution_outcomes(
+            pool,
+            &streamer_message.shards,
+            streamer_message.block.header.timestamp,
+            receipts_cache_arc.clone(),
+            adapters::execution_outcomes::ExecutionOutcomesOptions {
+                store_gas_profiles: opts.store_gas_profiles,
+                store_proofs: opts.store_outcome_proofs,
+            },
+        )
+        .await?;
+
+        // Staking events refer to the execution outcomes
+        adapters::staking::store_staking_events(
+            pool,
+            &streamer_message.shards,
+            streamer_message.block.header.height,
+            streamer_message.block.header.timestamp,
+            &opts.staking_pools,
+        )
+        .await?;
+
+        // Fees are summed up from the outcomes of the streamer message
+        adapters::block_fees::store_block_fees(
+            pool,
+            &streamer_message.block,
+            &streamer_message.shards,
+        )
+        .await
+    };
 
     // Accounts
     let accounts_future = async {
@@ -85,6 +148,7 @@ async fn handle_message(
             adapters::accounts::handle_accounts(
                 pool,
                 &shard.receipt_execution_outcomes,
+                &shard.state_changes,
                 streamer_message.block.header.height,
             )
         });
@@ -92,8 +156,69 @@ async fn handle_message(
         try_join_all(futures).await.map(|_| ())
     };
 
+    // Current state of the Accounts
+    let account_state_future = async {
+        let futures = streamer_message.shards.iter().map(|shard| {
+            adapters::accounts::handle_account_state(
+                pool,
+                &shard.state_changes,
+                streamer_message.block.header.height,
+            )
+        });
+
+        try_join_all(futures).await.map(|_| ())
+    };
+
+    // Contract deployments
+    let contract_deployments_future = adapters::contract_deployments::store_contract_deployments(
+        pool,
+        &streamer_message.shards,
+        streamer_message.block.header.height,
+        streamer_message.block.header.timestamp,
+        opts.store_contract_code,
+    );
+
+    // Who interacts with whom
+    let account_interactions_future = async {
+        if opts.aggregate_account_interactions {
+            adapters::aggregated::account_interactions::update_account_interactions(
+                pool,
+                &streamer_message.shards,
+                streamer_message.block.header.height,
+                streamer_message.block.header.timestamp,
+            )
+            .await
+        } else {
+            Ok(())
+        }
+    };
+
+    // Which contract methods are called
+    let contract_usage_future = async {
+        if opts.aggregate_contract_usage {
+            adapters::aggregated::contract_usage::update_contract_usage(
+                pool,
+                &streamer_message.shards,
+                streamer_message.block.header.height,
+                streamer_message.block.header.timestamp,
+            )
+            .await
+        } else {
+            Ok(())
+        }
+    };
+
     // Event-based entities (FT, NFT)
-    let assets_events_future = adapters::assets::events::store_events(pool, &streamer_message);
+    let assets_events_future = adapters::assets::events::store_events(
+        pool,
+        &streamer_message,
+        &assets_filter_arc,
+        adapters::assets::events::EventsOptions {
+            strict_events_validation: opts.strict_events_validation,
+            #[cfg(feature = "fungible_token_events")]
+            detect_legacy_ft_transfers: opts.detect_legacy_ft_transfers,
+        },
+    );
 
     if strict_mode {
         // AccessKeys
@@ -109,6 +234,15 @@ async fn handle_message(
             try_join_all(futures).await.map(|_| ())
         };
 
+        // Native balances history
+        let account_balance_changes_future =
+            adapters::account_balance_changes::store_account_balance_changes(
+                pool,
+                &streamer_message.shards,
+                streamer_message.block.header.height,
+                streamer_message.block.header.timestamp,
+            );
+
         // StateChange related to Account
         #[cfg(feature = "account_changes")]
         let account_changes_future = adapters::account_changes::store_account_changes(
@@ -121,7 +255,12 @@ async fn handle_message(
         try_join!(
             execution_outcomes_future,
             accounts_future,
+            account_state_future,
+            contract_deployments_future,
+            account_interactions_future,
+            contract_usage_future,
             access_keys_future,
+            account_balance_changes_future,
             assets_events_future,
             account_changes_future
         )?;
@@ -130,13 +269,22 @@ async fn handle_message(
         try_join!(
             execution_outcomes_future,
             accounts_future,
+            account_state_future,
+            contract_deployments_future,
+            account_interactions_future,
+            contract_usage_future,
             access_keys_future,
+            account_balance_changes_future,
             assets_events_future,
         )?;
     } else {
         try_join!(
             execution_outcomes_future,
             accounts_future,
+            account_state_future,
+            contract_deployments_future,
+            account_interactions_future,
+            contract_usage_future,
             assets_events_future
         )?;
     }
@@ -144,6 +292,15 @@ async fn handle_message(
     Ok(())
 }
 
+/// Start of the UTC day in nanoseconds
+fn day_start_timestamp(date: &chrono::NaiveDate) -> u64 {
+    let seconds = date
+        .and_hms_opt(0, 0, 0)
+        .expect("Midnight is expected to be valid")
+        .timestamp();
+    u64::try_from(seconds).expect("Dates before 1970 are not supported") * 1_000_000_000
+}
+
 async fn download_genesis_file(opts: &configs::Opts) -> anyhow::Result<std::path::PathBuf> {
     let res = reqwest::get(opts.genesis_file_url()).await?;
 
@@ -228,6 +385,95 @@ async fn main() -> anyhow::Result<()> {
     let receipts_cache_arc: receipts_cache::ReceiptsCacheArc =
         std::sync::Arc::new(Mutex::new(SizedCache::with_size(100_000)));
 
+    // Legacy FT transfers are restored only for the contracts listed in the assets filter file
+    #[cfg(feature = "fungible_token_events")]
+    anyhow::ensure!(
+        !opts.detect_legacy_ft_transfers || opts.assets_filter_file.is_some(),
+        "`--detect-legacy-ft-transfers` requires `--assets-filter-file` with `legacy_ft_contracts` list"
+    );
+
+    // Rules to skip the events of spam contracts (or to store only the events of specific ones)
+    let assets_filter_arc: adapters::assets::filter::ContractsFilterArc =
+        match &opts.assets_filter_file {
+            Some(path) => {
+                let assets_filter_arc = std::sync::Arc::new(tokio::sync::RwLock::new(
+                    adapters::assets::filter::ContractsFilter::from_file(path)?,
+                ));
+                tokio::spawn(adapters::assets::filter::watch_contracts_filter_file(
+                    path.clone(),
+                    assets_filter_arc.clone(),
+                ));
+                assets_filter_arc
+            }
+            None => std::sync::Arc::new(tokio::sync::RwLock::new(
+                adapters::assets::filter::ContractsFilter::default(),
+            )),
+        };
+
+    if let StartOptions::BackfillEvents { from, to } = opts.start_options() {
+        tracing::info!(
+            target: INDEXER_FOR_EXPLORER,
+            "Backfilling events for blocks {}..={}...",
+            from,
+            to,
+        );
+        return adapters::assets::backfill::backfill_events(
+            &pool,
+            *from,
+            *to,
+            &assets_filter_arc,
+            adapters::assets::events::EventsOptions {
+                strict_events_validation: opts.strict_events_validation,
+                #[cfg(feature = "fungible_token_events")]
+                detect_legacy_ft_transfers: opts.detect_legacy_ft_transfers,
+            },
+        )
+        .await;
+    }
+
+    if let StartOptions::RebuildAccountInteractions { from, to } = opts.start_options() {
+        tracing::info!(
+            target: INDEXER_FOR_EXPLORER,
+            "Rebuilding account interactions for days {}..={}...",
+            from,
+            to,
+        );
+        let stored_rows_count =
+            adapters::aggregated::account_interactions::rebuild_account_interactions(
+                &pool,
+                day_start_timestamp(from),
+                day_start_timestamp(to),
+            )
+            .await?;
+        tracing::info!(
+            target: INDEXER_FOR_EXPLORER,
+            "{} account interactions are stored",
+            stored_rows_count,
+        );
+        return Ok(());
+    }
+
+    if let StartOptions::RebuildContractUsage { from, to } = opts.start_options() {
+        tracing::info!(
+            target: INDEXER_FOR_EXPLORER,
+            "Rebuilding contract usage for days {}..={}...",
+            from,
+            to,
+        );
+        let stored_rows_count = adapters::aggregated::contract_usage::rebuild_contract_usage(
+            &pool,
+            day_start_timestamp(from),
+            day_start_timestamp(to),
+        )
+        .await?;
+        tracing::info!(
+            target: INDEXER_FOR_EXPLORER,
+            "{} contract usage rows are stored",
+            stored_rows_count,
+        );
+        return Ok(());
+    }
+
     tracing::info!(
         target: INDEXER_FOR_EXPLORER,
         "Starting Indexer for Explorer (lake)...",
@@ -243,17 +489,41 @@ async fn main() -> anyhow::Result<()> {
     let config: near_lake_framework::LakeConfig = opts.to_lake_config().await;
     let (sender, stream) = near_lake_framework::streamer(config);
 
-    let mut handlers = tokio_stream::wrappers::ReceiverStream::new(stream)
+    let mut stream = tokio_stream::wrappers::ReceiverStream::new(stream).peekable();
+
+    // The blocks come in order, so the epoch change is noticed here, before the concurrent handling.
+    // The epoch of the block before the first streamed one is taken from the database,
+    // otherwise the epoch started right at the first block after the restart would be missed
+    let mut last_epoch_id: Option<near_lake_framework::near_indexer_primitives::CryptoHash> =
+        match std::pin::Pin::new(&mut stream).peek().await {
+            Some(first_streamer_message) => {
+                adapters::epochs::get_block_epoch_id(
+                    &pool,
+                    &first_streamer_message.block.header.prev_hash,
+                )
+                .await?
+            }
+            None => None,
+        };
+
+    let mut handlers = stream
         .map(|streamer_message| {
             info!(
                 target: crate::INDEXER_FOR_EXPLORER,
                 "Block height {}", &streamer_message.block.header.height
             );
+            let epoch_id = streamer_message.block.header.epoch_id;
+            let previous_epoch_id = last_epoch_id
+                .replace(epoch_id)
+                .filter(|last_epoch_id| *last_epoch_id != epoch_id);
             handle_message(
                 &pool,
                 streamer_message,
                 strict_mode,
                 receipts_cache_arc.clone(),
+                assets_filter_arc.clone(),
+                &opts,
+                previous_epoch_id,
             )
         })
         .buffer_unordered(usize::from(opts.concurrency.get()));
@@ -278,3 +548,23 @@ async fn main() -> anyhow::Result<()> {
         Err(e) => Err(anyhow::Error::from(e)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn day_start_timestamp_is_utc_midnight_in_nanoseconds() {
+        let date = chrono::NaiveDate::from_ymd_opt(2020, 7, 21).unwrap();
+        assert_eq!(day_start_timestamp(&date), 1_595_289_600_000_000_000);
+
+        let epoch = chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+        assert_eq!(day_start_timestamp(&epoch), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Dates before 1970 are not supported")]
+    fn day_start_timestamp_rejects_dates_before_epoch() {
+        day_start_timestamp(&chrono::NaiveDate::from_ymd_opt(1969, 12, 31).unwrap());
+    }
+}